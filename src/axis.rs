@@ -1,7 +1,50 @@
-use crate::colors::WHITE;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+use sdl2::rect::Rect;
 use crate::config;
 use crate::coordinate::{CartesianCoordinate2D, PixelCoordinate2D};
 use crate::renderer::{Drawable, Renderer};
+use crate::scale::Scale;
+use crate::text::{HorizontalAnchor, TextStyle, VerticalAnchor};
+use crate::theme::{GridLineStyle, GridStyle};
+use crate::ticks::Ticks;
+use crate::error::GrapherError;
+
+const LABEL_SIZE: u16 = 20;
+const LABEL_MARGIN: i32 = 6;
+const TICK_LENGTH: i32 = 5;
+const TICK_LABEL_SIZE: u16 = 14;
+// Roughly how many pixels apart ticks should be, the nice step picked decides the exact spacing
+const X_TICK_SPACING: u32 = 100;
+const Y_TICK_SPACING: u32 = 80;
+// Blank and dash lengths in pixels
+const GRID_DASH: (u32, u32) = (4, 6);
+const GRID_DOT: (u32, u32) = (3, 1);
+// How much one wheel notch or key press zooms by
+const ZOOM_STEP: f32 = 1.25;
+// How far an arrow key moves the view, as a fraction of its size
+const PAN_FRACTION: f32 = 0.1;
+// Smaller selections are taken as a stray click instead of a box to zoom into
+const MIN_ZOOM_BOX: i32 = 5;
+
+/// What a mouse drag on the axis is doing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Drag {
+    /// Moving the view, holding where the mouse was last seen
+    Pan(PixelCoordinate2D),
+    /// Selecting a box to zoom into, from where the drag started to where the mouse is now
+    Zoom(PixelCoordinate2D, PixelCoordinate2D)
+}
+
+/// The part of an axis that changes while panning and zooming.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct View {
+    pixel_origin: PixelCoordinate2D,
+    x_scale: f32,
+    y_scale: f32,
+    step: f32
+}
 
 pub struct Axis2D {
     pixel_origin: PixelCoordinate2D,
@@ -10,46 +53,287 @@ pub struct Axis2D {
     step: f32,
     x_range: (f32, f32),
     y_range: (f32, f32),
-    objects: Vec<Box<dyn DrawableAxis2D>>
+    scales: (Scale, Scale),
+    objects: Vec<Box<dyn DrawableAxis2D>>,
+    labels: Option<(String, String)>,
+    viewport: Option<Rect>,
+    grid: (bool, bool),
+    interactive: bool,
+    home: View,
+    /// The last mouse position, in the pixels of the axis
+    mouse: PixelCoordinate2D,
+    drag: Option<Drag>
 }
 
 pub trait DrawableAxis2D {
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String>;
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError>;
     fn evaluate(&self, x: f32, y: f32) -> Option<(f32, f32)>;
 }
 
 impl Axis2D {
     pub fn new(pixel_origin: PixelCoordinate2D, x_scale: f32, y_scale: f32, step: f32) -> Axis2D {
-        let x_range = (
-            -pixel_origin.x as f32 / x_scale,
-            (config::WINDOW_WIDTH as i32 - pixel_origin.x) as f32 / x_scale
-        );
-        let y_range = (
-            -pixel_origin.y as f32 / y_scale,
-            (config::WINDOW_HEIGHT as i32 - pixel_origin.y) as f32 / y_scale
-        );
-        Axis2D {
+        let mut axis = Axis2D {
             pixel_origin,
             x_scale,
             y_scale,
             step,
-            x_range,
-            y_range,
-            objects: Vec::new()
+            x_range: (0.0, 0.0),
+            y_range: (0.0, 0.0),
+            scales: (Scale::Linear, Scale::Linear),
+            objects: Vec::new(),
+            labels: None,
+            viewport: None,
+            grid: (false, false),
+            interactive: false,
+            home: View { pixel_origin, x_scale, y_scale, step },
+            mouse: PixelCoordinate2D::origin(),
+            drag: None
+        };
+        axis.update_ranges();
+        axis
+    }
+
+    fn update_ranges(&mut self) {
+        let (width, height) = self.size();
+        let (x, y) = self.scales;
+        self.x_range = (
+            x.inverse(-self.pixel_origin.x as f32 / self.x_scale),
+            x.inverse((width as i32 - self.pixel_origin.x) as f32 / self.x_scale)
+        );
+        // y grows upwards, so the bottom of the axis is the lowest value
+        self.y_range = (
+            y.inverse((self.pixel_origin.y - height as i32) as f32 / self.y_scale),
+            y.inverse(self.pixel_origin.y as f32 / self.y_scale)
+        );
+    }
+
+    /// How values are spread along each axis. The origin and scales stay as they are, so with a log scale
+    /// the origin marks where the value is 1 and the scale is the pixels between powers of the base.
+    pub fn set_scales(&mut self, x: Scale, y: Scale) -> Result<(), GrapherError> {
+        for scale in [x, y] {
+            if let Scale::SymLog { threshold } = scale {
+                if !(threshold.is_finite() && threshold > 0.0) {
+                    return Err(GrapherError::InvalidData(format!("Symlog threshold must be a positive number, got {}", threshold)));
+                }
+            }
+        }
+        self.scales = (x, y);
+        self.update_ranges();
+        Ok(())
+    }
+
+    pub fn scales(&self) -> (Scale, Scale) {
+        self.scales
+    }
+
+    fn set_view(&mut self, view: View) {
+        self.pixel_origin = view.pixel_origin;
+        self.x_scale = view.x_scale;
+        self.y_scale = view.y_scale;
+        self.step = view.step;
+        self.update_ranges();
+    }
+
+    /// Lets the mouse and keyboard move the view: dragging pans, the wheel zooms around the cursor,
+    /// dragging with the right button zooms into the selected box, arrow keys pan, `+` and `-` zoom
+    /// and `0` or a double click goes back to where the axis started.
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+        self.drag = None;
+    }
+
+    /// Moves everything on the axis by a number of pixels.
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.pixel_origin = PixelCoordinate2D::new(self.pixel_origin.x + dx, self.pixel_origin.y + dy);
+        self.update_ranges();
+    }
+
+    /// Zooms in by `factor`, or out when it is below 1, keeping the point under `around` where it is.
+    pub fn zoom(&mut self, factor: f32, around: PixelCoordinate2D) {
+        self.zoom_axes(factor, factor, around);
+    }
+
+    /// Like `zoom` with a separate factor along each axis.
+    pub fn zoom_axes(&mut self, x_factor: f32, y_factor: f32, around: PixelCoordinate2D) {
+        if !(x_factor.is_finite() && y_factor.is_finite() && x_factor > 0.0 && y_factor > 0.0) {
+            return;
+        }
+        let origin = self.pixel_origin;
+        self.pixel_origin = PixelCoordinate2D::new(
+            around.x - ((around.x - origin.x) as f32 * x_factor).round() as i32,
+            around.y - ((around.y - origin.y) as f32 * y_factor).round() as i32
+        );
+        self.x_scale *= x_factor;
+        self.y_scale *= y_factor;
+        // Functions are sampled every step, keeping it the same number of pixels keeps curves as smooth as before
+        self.step /= x_factor;
+        self.update_ranges();
+    }
+
+    /// Fits the view to show exactly these ranges, ignored when either is empty.
+    pub fn zoom_to(&mut self, x_range: (f32, f32), y_range: (f32, f32)) {
+        let (x, y) = self.scales;
+        let x_range = (x.forward(x_range.0), x.forward(x_range.1));
+        let y_range = (y.forward(y_range.0), y.forward(y_range.1));
+        let (x_span, y_span) = (x_range.1 - x_range.0, y_range.1 - y_range.0);
+        if !(x_span.is_finite() && y_span.is_finite()) || x_span == 0.0 || y_span == 0.0 {
+            return;
+        }
+        let (width, height) = self.size();
+        let x_scale = width as f32 / x_span.abs();
+        let y_scale = height as f32 / y_span.abs();
+        self.step *= self.x_scale / x_scale;
+        self.x_scale = x_scale;
+        self.y_scale = y_scale;
+        self.pixel_origin = PixelCoordinate2D::new(
+            (-x_range.0.min(x_range.1) * x_scale).round() as i32,
+            (y_range.0.max(y_range.1) * y_scale).round() as i32
+        );
+        self.update_ranges();
+    }
+
+    /// Goes back to the origin, scales and step the axis was created with.
+    pub fn reset_view(&mut self) {
+        self.set_view(self.home);
+    }
+
+    fn contains(&self, position: PixelCoordinate2D) -> bool {
+        let (width, height) = self.size();
+        position.x >= 0 && position.y >= 0 && position.x < width as i32 && position.y < height as i32
+    }
+
+    /// Reacts to input when the axis is interactive, returning true when the view or the zoom box changed.
+    fn handle_input(&mut self, event: &Event) -> bool {
+        if !self.interactive {
+            return false;
+        }
+        let (width, height) = self.size();
+        let center = PixelCoordinate2D::new(width as i32 / 2, height as i32 / 2);
+        match *event {
+            Event::MouseMotion { x, y, .. } => {
+                self.mouse = self.window_to_local(PixelCoordinate2D::new(x, y));
+                match self.drag {
+                    Some(Drag::Pan(last)) => {
+                        self.pan(self.mouse.x - last.x, self.mouse.y - last.y);
+                        self.drag = Some(Drag::Pan(self.mouse));
+                        true
+                    },
+                    Some(Drag::Zoom(start, _)) => {
+                        self.drag = Some(Drag::Zoom(start, self.mouse));
+                        true
+                    },
+                    None => false
+                }
+            },
+            Event::MouseButtonDown { mouse_btn, clicks, x, y, .. } => {
+                let position = self.window_to_local(PixelCoordinate2D::new(x, y));
+                if !self.contains(position) {
+                    return false;
+                }
+                self.mouse = position;
+                match mouse_btn {
+                    MouseButton::Left if clicks >= 2 => {
+                        self.drag = None;
+                        self.reset_view();
+                        true
+                    },
+                    MouseButton::Left => {
+                        self.drag = Some(Drag::Pan(position));
+                        false
+                    },
+                    MouseButton::Right => {
+                        self.drag = Some(Drag::Zoom(position, position));
+                        true
+                    },
+                    _ => false
+                }
+            },
+            Event::MouseButtonUp { mouse_btn, .. } => match (mouse_btn, self.drag) {
+                (MouseButton::Left, Some(Drag::Pan(_))) => {
+                    self.drag = None;
+                    false
+                },
+                (MouseButton::Right, Some(Drag::Zoom(start, end))) => {
+                    self.drag = None;
+                    if (end.x - start.x).abs() >= MIN_ZOOM_BOX && (end.y - start.y).abs() >= MIN_ZOOM_BOX {
+                        let (start, end) = (self.to_cartesian(start), self.to_cartesian(end));
+                        self.zoom_to((start.x, end.x), (start.y, end.y));
+                    }
+                    // Either way the box has to be drawn away
+                    true
+                },
+                _ => false
+            },
+            Event::MouseWheel { y, direction, .. } => {
+                if y == 0 || !self.contains(self.mouse) {
+                    return false;
+                }
+                let notches = if direction == MouseWheelDirection::Flipped { -y } else { y };
+                self.zoom(ZOOM_STEP.powi(notches), self.mouse);
+                true
+            },
+            Event::KeyDown { keycode: Some(keycode), .. } => {
+                let pan_x = (width as f32 * PAN_FRACTION) as i32;
+                let pan_y = (height as f32 * PAN_FRACTION) as i32;
+                // The arrows move the view, so what is drawn moves the other way
+                match keycode {
+                    Keycode::Left => self.pan(pan_x, 0),
+                    Keycode::Right => self.pan(-pan_x, 0),
+                    Keycode::Up => self.pan(0, pan_y),
+                    Keycode::Down => self.pan(0, -pan_y),
+                    Keycode::Plus | Keycode::Equals | Keycode::KpPlus => self.zoom(ZOOM_STEP, center),
+                    Keycode::Minus | Keycode::KpMinus => self.zoom(1.0 / ZOOM_STEP, center),
+                    Keycode::Num0 | Keycode::Kp0 | Keycode::Home => self.reset_view(),
+                    _ => return false
+                }
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Confines the axis and everything on it to a rectangle of the window.
+    /// The pixel origin is then measured from the top left of the rectangle instead of the window.
+    pub fn set_viewport(&mut self, position: PixelCoordinate2D, w: u32, h: u32) {
+        self.viewport = Some(Rect::new(position.x, position.y, w, h));
+        self.update_ranges();
+    }
+
+    /// The size of the area the axis is drawn in, the whole window unless it has a viewport.
+    pub fn size(&self) -> (u32, u32) {
+        self.viewport.map_or((config::WINDOW_WIDTH, config::WINDOW_HEIGHT), |viewport| (viewport.width(), viewport.height()))
+    }
+
+    /// Converts a position in the window, like the mouse, into the pixels the axis draws in.
+    pub fn window_to_local(&self, position: PixelCoordinate2D) -> PixelCoordinate2D {
+        match self.viewport {
+            Some(viewport) => PixelCoordinate2D::new(position.x - viewport.x(), position.y - viewport.y()),
+            None => position
         }
     }
 
     pub fn to_pixel(&self, position: CartesianCoordinate2D) -> PixelCoordinate2D {
+        let (x, y) = self.scales;
         PixelCoordinate2D::new(
-            (position.x * self.x_scale) as i32 + self.pixel_origin.x,
-            (position.y * self.y_scale * -1.0) as i32 + self.pixel_origin.y
+            (x.forward(position.x) * self.x_scale) as i32 + self.pixel_origin.x,
+            (y.forward(position.y) * self.y_scale * -1.0) as i32 + self.pixel_origin.y
+        )
+    }
+
+    /// Like `to_pixel` but keeps the fraction of a pixel, for drawing that can place things between pixels.
+    pub fn to_pixel_precise(&self, position: CartesianCoordinate2D) -> (f32, f32) {
+        let (x, y) = self.scales;
+        (
+            x.forward(position.x) * self.x_scale + self.pixel_origin.x as f32,
+            self.pixel_origin.y as f32 - y.forward(position.y) * self.y_scale
         )
     }
 
     pub fn to_cartesian(&self, position: PixelCoordinate2D) -> CartesianCoordinate2D {
+        let (x, y) = self.scales;
         CartesianCoordinate2D::new(
-            (position.x - self.pixel_origin.x) as f32 / self.x_scale,
-            (position.y - self.pixel_origin.y) as f32 / self.y_scale * -1.0
+            x.inverse((position.x - self.pixel_origin.x) as f32 / self.x_scale),
+            y.inverse((position.y - self.pixel_origin.y) as f32 / self.y_scale * -1.0)
         )
     }
 
@@ -61,12 +345,19 @@ impl Axis2D {
         self.y_range
     }
 
+    // Steps are taken in the space the scales spread evenly, the same as the ranges for a linear axis
     pub fn x_steps(&self) -> i32 {
-        ((self.x_range.1 - self.x_range.0) / self.step) as i32
+        (self.size().0 as f32 / self.x_scale / self.step) as i32
     }
 
     pub fn y_steps(&self) -> i32 {
-        ((self.y_range.1 - self.y_range.0) / self.step) as i32
+        (self.size().1 as f32 / self.y_scale / self.step) as i32
+    }
+
+    /// Where to evaluate functions across the x range, evenly spaced on screen whatever the scale.
+    pub fn x_samples(&self) -> Vec<f32> {
+        let start = -self.pixel_origin.x as f32 / self.x_scale;
+        (0..self.x_steps()).map(|i| self.scales.0.inverse(start + i as f32 * self.step)).collect()
     }
 
     pub fn range(&self) -> ((f32, f32), (f32, f32)) {
@@ -81,6 +372,151 @@ impl Axis2D {
         self.step
     }
 
+    /// Tick positions for the x axis, spaced to suit the current range and size.
+    pub fn x_ticks(&self) -> Ticks {
+        Ticks::for_scale(self.scales.0, self.x_range.0, self.x_range.1, (self.size().0 / X_TICK_SPACING).max(2) as usize)
+    }
+
+    pub fn y_ticks(&self) -> Ticks {
+        Ticks::for_scale(self.scales.1, self.y_range.0, self.y_range.1, (self.size().1 / Y_TICK_SPACING).max(2) as usize)
+    }
+
+    /// Where the axis lines are drawn, through the origin or along the nearest edge when the origin is off screen.
+    /// A log scale has no zero, so the other axis line goes along its lower edge.
+    pub fn spines(&self) -> (i32, i32) {
+        let (width, height) = self.size();
+        (
+            if self.scales.0.has_zero() { self.pixel_origin.x.clamp(0, width as i32 - 1) } else { 0 },
+            if self.scales.1.has_zero() { self.pixel_origin.y.clamp(0, height as i32 - 1) } else { height as i32 - 1 }
+        )
+    }
+
+    fn origin_visible(&self) -> bool {
+        let (spine_x, spine_y) = self.spines();
+        self.scales.0.has_zero() && self.scales.1.has_zero() && spine_x == self.pixel_origin.x && spine_y == self.pixel_origin.y
+    }
+
+    /// The labels written along each axis. Zero sits where the two lines cross, so while that crossing
+    /// is on screen it is written once next to it instead of on both axes.
+    fn tick_labels(&self) -> (Vec<(f32, String)>, Vec<(f32, String)>) {
+        let origin_visible = self.origin_visible();
+        let labels = |ticks: Ticks| -> Vec<(f32, String)> {
+            ticks.values.iter()
+                .filter(|&&value| !(value == 0.0 && origin_visible))
+                .map(|&value| (value, ticks.label(value)))
+                .collect()
+        };
+        (labels(self.x_ticks()), labels(self.y_ticks()))
+    }
+
+    fn draw_ticks(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let height = self.size().1;
+        let (spine_x, spine_y) = self.spines();
+        let theme = *renderer.theme();
+        let style = TextStyle::new(theme.text, TICK_LABEL_SIZE);
+        let (x_labels, y_labels) = self.tick_labels();
+
+        for &x in &self.x_ticks().values {
+            let pixel_x = self.to_pixel(CartesianCoordinate2D::new(x, 0.0)).x;
+            renderer.draw_line(
+                PixelCoordinate2D::new(pixel_x, spine_y - TICK_LENGTH),
+                PixelCoordinate2D::new(pixel_x, spine_y + TICK_LENGTH),
+                theme.foreground
+            )?;
+        }
+        let label_height = renderer.text_size("0", TICK_LABEL_SIZE)?.1 as i32;
+        // Labels go below the line unless that would push them off the bottom
+        let below = spine_y + TICK_LENGTH + label_height <= height as i32;
+        for (x, label) in &x_labels {
+            let pixel_x = self.to_pixel(CartesianCoordinate2D::new(*x, 0.0)).x;
+            let (y, vertical) = if below { (spine_y + TICK_LENGTH, VerticalAnchor::Top) } else { (spine_y - TICK_LENGTH, VerticalAnchor::Bottom) };
+            renderer.draw_text_styled(
+                label,
+                PixelCoordinate2D::new(pixel_x, y),
+                &style.clone().with_anchor(HorizontalAnchor::Center, vertical)
+            )?;
+        }
+
+        for &y in &self.y_ticks().values {
+            let pixel_y = self.to_pixel(CartesianCoordinate2D::new(0.0, y)).y;
+            renderer.draw_line(
+                PixelCoordinate2D::new(spine_x - TICK_LENGTH, pixel_y),
+                PixelCoordinate2D::new(spine_x + TICK_LENGTH, pixel_y),
+                theme.foreground
+            )?;
+        }
+        let mut label_width = 0;
+        for (_, label) in &y_labels {
+            label_width = label_width.max(renderer.text_size(label, TICK_LABEL_SIZE)?.0 as i32);
+        }
+        // Labels go left of the line unless that would push them off the left edge
+        let left = spine_x - TICK_LENGTH - label_width >= 0;
+        for (y, label) in &y_labels {
+            let pixel_y = self.to_pixel(CartesianCoordinate2D::new(0.0, *y)).y;
+            let (x, horizontal) = if left { (spine_x - TICK_LENGTH, HorizontalAnchor::Right) } else { (spine_x + TICK_LENGTH, HorizontalAnchor::Left) };
+            renderer.draw_text_styled(
+                label,
+                PixelCoordinate2D::new(x, pixel_y),
+                &style.clone().with_anchor(horizontal, VerticalAnchor::Middle)
+            )?;
+        }
+
+        if self.origin_visible() {
+            renderer.draw_text_styled(
+                "0",
+                PixelCoordinate2D::new(spine_x - TICK_LENGTH, spine_y + TICK_LENGTH),
+                &style.with_anchor(HorizontalAnchor::Right, VerticalAnchor::Top)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Draws lines across the whole axis at the ticks, the major ones at every labelled tick
+    /// and the minor ones between them. Both are styled by the renderer's theme.
+    pub fn set_grid(&mut self, major: bool, minor: bool) {
+        self.grid = (major, minor);
+    }
+
+    fn draw_grid_lines(&self, renderer: &mut Renderer, x_values: &[f32], y_values: &[f32], style: GridStyle) -> Result<(), GrapherError> {
+        let (width, height) = self.size();
+        let mut lines = Vec::new();
+        for &x in x_values {
+            let pixel_x = self.to_pixel(CartesianCoordinate2D::new(x, 0.0)).x;
+            lines.push((PixelCoordinate2D::new(pixel_x, 0), PixelCoordinate2D::new(pixel_x, height as i32)));
+        }
+        for &y in y_values {
+            let pixel_y = self.to_pixel(CartesianCoordinate2D::new(0.0, y)).y;
+            lines.push((PixelCoordinate2D::new(0, pixel_y), PixelCoordinate2D::new(width as i32, pixel_y)));
+        }
+        for (start, end) in lines {
+            match style.line {
+                GridLineStyle::Solid => renderer.draw_line(start, end, style.color)?,
+                // Anchored at the corner so the pattern lines up from one gridline to the next
+                GridLineStyle::Dashed => renderer.draw_dashed_line_anchored(start, end, style.color, GRID_DASH.0, GRID_DASH.1, PixelCoordinate2D::origin())?,
+                GridLineStyle::Dotted => renderer.draw_dashed_line_anchored(start, end, style.color, GRID_DOT.0, GRID_DOT.1, PixelCoordinate2D::origin())?
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_grid(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
+        let (x_ticks, y_ticks) = (self.x_ticks(), self.y_ticks());
+        // Minor first so the major lines are drawn over them
+        if self.grid.1 {
+            self.draw_grid_lines(renderer, &x_ticks.minor(), &y_ticks.minor(), theme.minor_grid)?;
+        }
+        if self.grid.0 {
+            self.draw_grid_lines(renderer, &x_ticks.values, &y_ticks.values, theme.major_grid)?;
+        }
+        Ok(())
+    }
+
+    /// Names for the x and y axes drawn at their ends, written in math markup so `\sigma` and `\tau` work.
+    pub fn set_labels(&mut self, x_label: &str, y_label: &str) {
+        self.labels = Some((x_label.to_string(), y_label.to_string()));
+    }
+
     pub fn add_object(&mut self, object: Box<dyn DrawableAxis2D>) {
         self.objects.push(object);
     }
@@ -105,23 +541,108 @@ impl Axis2D {
 }
 
 impl Drawable for Axis2D {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
         // renderer.draw_line(0, self.pixel_origin.y, config::WINDOW_WIDTH as i32, self.pixel_origin.y, WHITE)?;
         // renderer.draw_line(self.pixel_origin.x, 0, self.pixel_origin.x, config::WINDOW_HEIGHT as i32, WHITE)?;
+        if let Some(viewport) = self.viewport {
+            renderer.push_viewport(PixelCoordinate2D::new(viewport.x(), viewport.y()), viewport.width(), viewport.height())?;
+        }
+        let (width, height) = self.size();
+        let (spine_x, spine_y) = self.spines();
+        let theme = *renderer.theme();
+        self.draw_grid(renderer)?;
         renderer.draw_line(
-            PixelCoordinate2D::new(0, self.pixel_origin.y),
-            PixelCoordinate2D::new(config::WINDOW_WIDTH as i32, self.pixel_origin.y),
-            WHITE
+            PixelCoordinate2D::new(0, spine_y),
+            PixelCoordinate2D::new(width as i32, spine_y),
+            theme.foreground
         )?;
         renderer.draw_line(
-            PixelCoordinate2D::new(self.pixel_origin.x, 0),
-            PixelCoordinate2D::new(self.pixel_origin.x, config::WINDOW_HEIGHT as i32),
-            WHITE
+            PixelCoordinate2D::new(spine_x, 0),
+            PixelCoordinate2D::new(spine_x, height as i32),
+            theme.foreground
         )?;
+        self.draw_ticks(renderer)?;
+        if let Some((x_label, y_label)) = &self.labels {
+            // Above the x axis and right of the y axis, the tick labels take the other sides
+            renderer.draw_text_styled(
+                x_label,
+                PixelCoordinate2D::new(width as i32 - LABEL_MARGIN, spine_y - LABEL_MARGIN),
+                &TextStyle::new(theme.text, LABEL_SIZE).with_anchor(HorizontalAnchor::Right, VerticalAnchor::Bottom).with_math()
+            )?;
+            renderer.draw_text_styled(
+                y_label,
+                PixelCoordinate2D::new(spine_x + LABEL_MARGIN, LABEL_MARGIN),
+                &TextStyle::new(theme.text, LABEL_SIZE).with_math()
+            )?;
+        }
 
-        self.objects.iter().for_each(|object| {
-            object.draw(renderer, self).unwrap();
-        });
-        Ok(())
+        // Keep going after a failed object so the viewport is still popped, then report the first failure
+        let mut result = Ok(());
+        for object in &self.objects {
+            let drawn = object.draw(renderer, self);
+            if result.is_ok() {
+                result = drawn;
+            }
+        }
+        if let Some(Drag::Zoom(start, end)) = self.drag {
+            let corner = PixelCoordinate2D::new(start.x.min(end.x), start.y.min(end.y));
+            let drawn = renderer.draw_rect(corner, start.x.abs_diff(end.x), start.y.abs_diff(end.y), theme.highlight);
+            if result.is_ok() {
+                result = drawn;
+            }
+        }
+        if self.viewport.is_some() {
+            renderer.pop_viewport()?;
+        }
+        result
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        self.handle_input(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_scales_rejects_unusable_symlog_thresholds() {
+        let mut axis = Axis2D::new(PixelCoordinate2D::new(100, 100), 10.0, 10.0, 0.1);
+        for threshold in [0.0, -2.0, f32::NAN, f32::INFINITY] {
+            let result = axis.set_scales(Scale::SymLog { threshold }, Scale::Linear);
+            assert!(matches!(result, Err(GrapherError::InvalidData(_))), "threshold {} was accepted", threshold);
+            assert_eq!(axis.scales(), (Scale::Linear, Scale::Linear));
+        }
+        assert!(axis.set_scales(Scale::SymLog { threshold: 0.5 }, Scale::Log10).is_ok());
+        assert_eq!(axis.scales(), (Scale::SymLog { threshold: 0.5 }, Scale::Log10));
+    }
+
+    fn has_zero_label(labels: &[(f32, String)]) -> bool {
+        labels.iter().any(|(value, label)| *value == 0.0 && label == "0")
+    }
+
+    #[test]
+    fn zero_is_labelled_once_while_the_origin_is_on_screen() {
+        let mut axis = Axis2D::new(PixelCoordinate2D::new(100, 100), 20.0, 20.0, 0.1);
+        axis.set_viewport(PixelCoordinate2D::origin(), 200, 200);
+        let (x_labels, y_labels) = axis.tick_labels();
+        assert!(!has_zero_label(&x_labels) && !has_zero_label(&y_labels));
+        assert!(!x_labels.is_empty() && !y_labels.is_empty());
+    }
+
+    #[test]
+    fn zero_keeps_its_label_when_the_origin_is_panned_off_an_edge() {
+        let mut axis = Axis2D::new(PixelCoordinate2D::new(100, 100), 20.0, 20.0, 0.1);
+        axis.set_viewport(PixelCoordinate2D::origin(), 200, 200);
+        // The x axis line is pinned to the bottom edge while the y axis line is still in place
+        axis.pan(0, 300);
+        assert_eq!(axis.spines(), (100, 199));
+        assert!(has_zero_label(&axis.tick_labels().0));
+
+        // The y axis line is pinned to the left edge while the x axis line is in place
+        axis.pan(-300, -300);
+        assert_eq!(axis.spines(), (0, 100));
+        assert!(has_zero_label(&axis.tick_labels().1));
     }
 }
@@ -0,0 +1,366 @@
+use std::any::Any;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, SurfaceCanvas, WindowCanvas};
+use sdl2::surface::Surface;
+use crate::coordinate::PixelCoordinate2D;
+use crate::font::FontManager;
+use crate::path::Path;
+use crate::raster;
+use crate::stroke::{self, StrokeStyle};
+use crate::error::GrapherError;
+
+/// The primitives every output format has to provide, anything more complex is built from these by the `Renderer`.
+pub trait Backend {
+    fn size(&self) -> Result<(u32, u32), GrapherError>;
+    fn clear(&mut self, color: Color);
+    fn present(&mut self);
+    fn draw_point(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), GrapherError>;
+    fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError>;
+    fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError>;
+    fn fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError>;
+    fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), GrapherError>;
+    fn draw_text(&mut self, fonts: &mut FontManager, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError>;
+
+    /// Selects how lines and circles are rasterized, vector backends are always smooth and ignore this.
+    fn set_antialiasing(&mut self, _mode: Antialiasing) {}
+
+    /// Draws a line between sub-pixel positions, backends that can not place anything between pixels round them.
+    fn draw_line_precise(&mut self, start: (f32, f32), end: (f32, f32), color: Color) -> Result<(), GrapherError> {
+        self.draw_line(
+            PixelCoordinate2D::new(start.0.round() as i32, start.1.round() as i32),
+            PixelCoordinate2D::new(end.0.round() as i32, end.1.round() as i32),
+            color
+        )
+    }
+
+    /// Draws a single line of text with its top left corner at a sub-pixel position,
+    /// turned `rotation` radians counterclockwise around that corner.
+    /// Backends that can not rotate text draw it level at the rounded position.
+    fn draw_text_rotated(&mut self, fonts: &mut FontManager, text: &str, position: (f32, f32), color: Color, size: u16, _rotation: f32) -> Result<(), GrapherError> {
+        self.draw_text(fonts, text, PixelCoordinate2D::new(position.0.round() as i32, position.1.round() as i32), color, size)
+    }
+
+    /// Strokes a polyline through sub-pixel positions, joining the last point back to the first when `closed`.
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], closed: bool, style: &StrokeStyle) -> Result<(), GrapherError>;
+
+    fn stroke_circle(&mut self, center: (f32, f32), radius: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.stroke_polyline(&stroke::circle_polygon(center, radius), true, style)
+    }
+
+    /// Fills a polygon through sub-pixel positions with the nonzero winding rule, so it may be concave or cross itself.
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) -> Result<(), GrapherError>;
+
+    fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: Color) -> Result<(), GrapherError> {
+        self.fill_polygon(&stroke::circle_polygon(center, radius), color)
+    }
+
+    /// Strokes every subpath of the path, curves are flattened unless the backend can draw them directly.
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle) -> Result<(), GrapherError> {
+        for (points, closed) in path.flatten() {
+            self.stroke_polyline(&points, closed, style)?;
+        }
+        Ok(())
+    }
+
+    /// Fills the inside of the path, by default each subpath is filled on its own so they can not cut holes in each other.
+    fn fill_path(&mut self, path: &Path, color: Color) -> Result<(), GrapherError> {
+        for (points, _) in path.flatten() {
+            self.fill_polygon(&points, color)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the origin of everything drawn afterwards to `origin` and hides anything outside of `clip`,
+    /// both in pixels of the whole output. A `clip` of `None` leaves everything visible.
+    fn set_view(&mut self, origin: (i32, i32), clip: Option<Rect>) -> Result<(), GrapherError>;
+
+    /// The id of the window being drawn to, `None` for anything that is not on screen.
+    fn window_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Reads back a region of the frame as tightly packed RGBA bytes, only raster backends support this.
+    fn read_pixels(&self, _rect: Option<Rect>) -> Result<Vec<u8>, GrapherError> {
+        Err(GrapherError::InvalidData("This backend does not support reading pixels".to_string()))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+/// `writeln!` for the documents the vector backends build up in a `String`, which can not fail so there is no result to handle.
+macro_rules! push_line {
+    ($document:expr, $($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        $document.push_str(&line);
+        $document.push('\n');
+    }};
+}
+pub(crate) use push_line;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Antialiasing {
+    Off,
+    /// Xiaolin Wu style lines and circles, edge pixels are blended by how much of them the shape covers
+    Wu
+}
+
+pub enum SdlCanvas {
+    Window(WindowCanvas),
+    Offscreen(SurfaceCanvas<'static>)
+}
+
+/// Draws through SDL, either into a window or an in-memory surface.
+pub struct SdlBackend {
+    canvas: SdlCanvas,
+    antialiasing: Antialiasing
+}
+
+macro_rules! with_canvas {
+    ($target:expr, $canvas:ident => $body:expr) => {
+        match $target {
+            SdlCanvas::Window($canvas) => $body,
+            SdlCanvas::Offscreen($canvas) => $body
+        }
+    };
+}
+
+impl SdlBackend {
+    pub fn window(mut canvas: WindowCanvas) -> SdlBackend {
+        // Everything is blended so translucent colors work, opaque ones draw the same as without blending
+        canvas.set_blend_mode(BlendMode::Blend);
+        SdlBackend { canvas: SdlCanvas::Window(canvas), antialiasing: Antialiasing::Off }
+    }
+
+    pub fn offscreen(width: u32, height: u32) -> Result<SdlBackend, GrapherError> {
+        let surface = Surface::new(width, height, PixelFormatEnum::RGBA32).map_err(GrapherError::Sdl)?;
+        let mut canvas = surface.into_canvas().map_err(GrapherError::Sdl)?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        Ok(SdlBackend { canvas: SdlCanvas::Offscreen(canvas), antialiasing: Antialiasing::Off })
+    }
+
+    pub fn set_scale(&mut self, x: f32, y: f32) -> Result<(), GrapherError> {
+        with_canvas!(&mut self.canvas, canvas => canvas.set_scale(x, y).map_err(GrapherError::Sdl))
+    }
+
+    /// The size in the units drawing happens in, which is smaller than the output when scaled up.
+    fn logical_size(&self) -> Result<(u32, u32), GrapherError> {
+        with_canvas!(&self.canvas, canvas => {
+            let (width, height) = canvas.output_size().map_err(GrapherError::Sdl)?;
+            let (scale_x, scale_y) = canvas.scale();
+            Ok(((width as f32 / scale_x).ceil() as u32, (height as f32 / scale_y).ceil() as u32))
+        })
+    }
+
+    /// Blends each pixel over what is already there using its coverage times the color's alpha.
+    fn plot_coverage(&mut self, pixels: &[(i32, i32, f32)], color: Color) -> Result<(), GrapherError> {
+        with_canvas!(&mut self.canvas, canvas => {
+            for &(x, y, coverage) in pixels {
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let alpha = (color.a as f32 * coverage.min(1.0)).round() as u8;
+                canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, alpha));
+                canvas.draw_point(Point::new(x, y)).map_err(GrapherError::Sdl)?;
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Backend for SdlBackend {
+    fn size(&self) -> Result<(u32, u32), GrapherError> {
+        with_canvas!(&self.canvas, canvas => canvas.output_size().map_err(GrapherError::Sdl))
+    }
+
+    fn clear(&mut self, color: Color) {
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            canvas.clear();
+        })
+    }
+
+    fn present(&mut self) {
+        with_canvas!(&mut self.canvas, canvas => canvas.present())
+    }
+
+    fn draw_point(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            canvas.draw_point(Point::new(position.x, position.y)).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        if self.antialiasing == Antialiasing::Wu {
+            return self.draw_line_precise(
+                (position1.x as f32, position1.y as f32),
+                (position2.x as f32, position2.y as f32),
+                color
+            );
+        }
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            canvas.draw_line(
+                Point::new(position1.x, position1.y),
+                Point::new(position2.x, position2.y)
+            ).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            canvas.draw_rect(Rect::new(position.x, position.y, w, h)).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            canvas.fill_rect(Rect::new(position.x, position.y, w, h)).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), GrapherError> {
+        if self.antialiasing == Antialiasing::Wu {
+            let mut pixels = Vec::new();
+            raster::wu_circle((position.x as f32, position.y as f32), radius as f32, |x, y, coverage| pixels.push((x, y, coverage)));
+            return self.plot_coverage(&pixels, color);
+        }
+        with_canvas!(&mut self.canvas, canvas => {
+            canvas.set_draw_color(color);
+            // Draw a circle as a series of dots
+            let mut x = 0;
+            let mut y = radius as i32;
+            let mut d = 3 - 2 * radius as i32;
+            while x <= y {
+                canvas.draw_point(Point::new(position.x + x, position.y + y)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x + x, position.y - y)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x - x, position.y + y)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x - x, position.y - y)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x + y, position.y + x)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x + y, position.y - x)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x - y, position.y + x)).map_err(GrapherError::Sdl)?;
+                canvas.draw_point(Point::new(position.x - y, position.y - x)).map_err(GrapherError::Sdl)?;
+                if d < 0 {
+                    d += 4 * x + 6;
+                } else {
+                    d += 4 * (x - y) + 10;
+                    y -= 1;
+                }
+                x += 1;
+            }
+        });
+        Ok(())
+    }
+
+    fn draw_text(&mut self, fonts: &mut FontManager, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let surface = fonts.render(text, size, color)?;
+        with_canvas!(&mut self.canvas, canvas => {
+            let texture_creator = canvas.texture_creator();
+            let mut texture = texture_creator.create_texture_from_surface(surface)?;
+            texture.set_alpha_mod(color.a);
+            canvas.copy(&texture, None, Rect::new(position.x, position.y, surface.width(), surface.height())).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn set_antialiasing(&mut self, mode: Antialiasing) {
+        self.antialiasing = mode;
+    }
+
+    fn draw_text_rotated(&mut self, fonts: &mut FontManager, text: &str, position: (f32, f32), color: Color, size: u16, rotation: f32) -> Result<(), GrapherError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let surface = fonts.render(text, size, color)?;
+        with_canvas!(&mut self.canvas, canvas => {
+            let texture_creator = canvas.texture_creator();
+            let mut texture = texture_creator.create_texture_from_surface(surface)?;
+            texture.set_alpha_mod(color.a);
+            let target = Rect::new(position.0.round() as i32, position.1.round() as i32, surface.width(), surface.height());
+            // SDL turns clockwise in degrees
+            canvas.copy_ex(&texture, None, target, -rotation.to_degrees() as f64, Point::new(0, 0), false, false).map_err(GrapherError::Sdl)?;
+        });
+        Ok(())
+    }
+
+    fn draw_line_precise(&mut self, start: (f32, f32), end: (f32, f32), color: Color) -> Result<(), GrapherError> {
+        match self.antialiasing {
+            Antialiasing::Off => self.draw_line(
+                PixelCoordinate2D::new(start.0.round() as i32, start.1.round() as i32),
+                PixelCoordinate2D::new(end.0.round() as i32, end.1.round() as i32),
+                color
+            ),
+            Antialiasing::Wu => {
+                let mut pixels = Vec::new();
+                raster::wu_line(start, end, self.logical_size()?, |x, y, coverage| pixels.push((x, y, coverage)));
+                self.plot_coverage(&pixels, color)
+            }
+        }
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], closed: bool, style: &StrokeStyle) -> Result<(), GrapherError> {
+        // Pixel coordinates name the pixel, the stroke is centered on the middle of it
+        let points: Vec<(f32, f32)> = points.iter().map(|&(x, y)| (x + 0.5, y + 0.5)).collect();
+        let polygons = stroke::outline(&points, closed, style);
+        let mut pixels = Vec::new();
+        raster::fill_polygons(&polygons, self.antialiasing == Antialiasing::Wu, self.logical_size()?, |x, y, coverage| pixels.push((x, y, coverage)));
+        self.plot_coverage(&pixels, style.color)
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) -> Result<(), GrapherError> {
+        let points: Vec<(f32, f32)> = points.iter().map(|&(x, y)| (x + 0.5, y + 0.5)).collect();
+        let mut pixels = Vec::new();
+        raster::fill_polygons(&[points], self.antialiasing == Antialiasing::Wu, self.logical_size()?, |x, y, coverage| pixels.push((x, y, coverage)));
+        self.plot_coverage(&pixels, color)
+    }
+
+    fn fill_path(&mut self, path: &Path, color: Color) -> Result<(), GrapherError> {
+        // Filled together so the winding of inner subpaths can cut holes
+        let polygons: Vec<Vec<(f32, f32)>> = path.flatten().into_iter()
+            .map(|(points, _)| points.into_iter().map(|(x, y)| (x + 0.5, y + 0.5)).collect())
+            .collect();
+        let mut pixels = Vec::new();
+        raster::fill_polygons(&polygons, self.antialiasing == Antialiasing::Wu, self.logical_size()?, |x, y, coverage| pixels.push((x, y, coverage)));
+        self.plot_coverage(&pixels, color)
+    }
+
+    fn set_view(&mut self, origin: (i32, i32), clip: Option<Rect>) -> Result<(), GrapherError> {
+        let (width, height) = self.logical_size()?;
+        with_canvas!(&mut self.canvas, canvas => {
+            // The viewport only moves the origin, it runs past the edge of the output so it never clips anything itself
+            if origin == (0, 0) {
+                canvas.set_viewport(None);
+            } else {
+                canvas.set_viewport(Rect::new(origin.0, origin.1, width, height));
+            }
+            // SDL measures the clip rectangle from the viewport
+            canvas.set_clip_rect(clip.map(|clip| Rect::new(clip.x() - origin.0, clip.y() - origin.1, clip.width(), clip.height())));
+        });
+        Ok(())
+    }
+
+    fn window_id(&self) -> Option<u32> {
+        match &self.canvas {
+            SdlCanvas::Window(canvas) => Some(canvas.window().id()),
+            SdlCanvas::Offscreen(_) => None
+        }
+    }
+
+    fn read_pixels(&self, rect: Option<Rect>) -> Result<Vec<u8>, GrapherError> {
+        with_canvas!(&self.canvas, canvas => canvas.read_pixels(rect, PixelFormatEnum::RGBA32).map_err(GrapherError::Sdl))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
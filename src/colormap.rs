@@ -0,0 +1,238 @@
+use sdl2::pixels::Color;
+use crate::axis::{Axis2D, DrawableAxis2D};
+use crate::colors::{self, Gradient};
+use crate::coordinate::PixelCoordinate2D;
+use crate::error::GrapherError;
+use crate::renderer::Renderer;
+use crate::text::{HorizontalAnchor, TextStyle, VerticalAnchor};
+
+/// Evenly spaced samples of each map, blended in between, close enough to the originals to read values off.
+const VIRIDIS: [u32; 11] = [
+    0x440154, 0x482475, 0x414487, 0x355f8d, 0x2a788e, 0x21918c, 0x22a884, 0x44bf70, 0x7ad151, 0xbddf26, 0xfde725
+];
+const MAGMA: [u32; 11] = [
+    0x000004, 0x140e36, 0x3b0f70, 0x641a80, 0x8c2981, 0xb73779, 0xde4968, 0xf7705c, 0xfe9f6d, 0xfecf92, 0xfcfdbf
+];
+const PLASMA: [u32; 11] = [
+    0x0d0887, 0x41049d, 0x6a00a8, 0x8f0da4, 0xb12a90, 0xcc4778, 0xe16462, 0xf2844b, 0xfca636, 0xfcce25, 0xf0f921
+];
+const CIVIDIS: [u32; 10] = [
+    0x00204d, 0x00336f, 0x39486b, 0x575c6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c, 0xe4cf5b, 0xffea46
+];
+const COOLWARM: [u32; 11] = [
+    0x3b4cc0, 0x5977e3, 0x7b9ff9, 0x9ebeff, 0xc0d4f5, 0xdddcdc, 0xf2cbb7, 0xf7ac8e, 0xee8468, 0xd65244, 0xb40426
+];
+const GRAYSCALE: [u32; 2] = [0x000000, 0xffffff];
+
+/// The Okabe-Ito palette, eight colors that stay distinct for every common kind of colour blindness.
+pub const OKABE_ITO: [Color; 8] = [
+    Color::RGB(0, 0, 0),
+    Color::RGB(230, 159, 0),
+    Color::RGB(86, 180, 233),
+    Color::RGB(0, 158, 115),
+    Color::RGB(240, 228, 66),
+    Color::RGB(0, 114, 178),
+    Color::RGB(213, 94, 0),
+    Color::RGB(204, 121, 167)
+];
+
+/// Paul Tol's bright palette, colour blind safe and without black so it also works on dark backgrounds.
+pub const TOL_BRIGHT: [Color; 7] = [
+    Color::RGB(68, 119, 170),
+    Color::RGB(238, 102, 119),
+    Color::RGB(34, 136, 51),
+    Color::RGB(204, 187, 68),
+    Color::RGB(102, 204, 238),
+    Color::RGB(170, 51, 119),
+    Color::RGB(187, 187, 187)
+];
+
+/// The color for the `index`th category, starting over once the palette runs out. `None` for an empty palette.
+pub fn categorical(palette: &[Color], index: usize) -> Option<Color> {
+    if palette.is_empty() {
+        return None;
+    }
+    Some(palette[index % palette.len()])
+}
+
+/// Continuous maps from a value to a color. Viridis, magma, plasma and cividis get steadily lighter
+/// so they read correctly in grayscale and for colour blind readers, coolwarm is for values around a middle.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Colormap {
+    Viridis,
+    Magma,
+    Plasma,
+    Cividis,
+    Coolwarm,
+    Grayscale
+}
+
+impl Colormap {
+    fn samples(self) -> &'static [u32] {
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Plasma => &PLASMA,
+            Colormap::Cividis => &CIVIDIS,
+            Colormap::Coolwarm => &COOLWARM,
+            Colormap::Grayscale => &GRAYSCALE
+        }
+    }
+
+    /// The color `t` of the way along the map, clamped to 0 to 1.
+    pub fn at(self, t: f32) -> Color {
+        let samples = self.samples();
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let position = t * (samples.len() - 1) as f32;
+        let index = (position as usize).min(samples.len() - 2);
+        colors::lerp(hex(samples[index]), hex(samples[index + 1]), position - index as f32)
+    }
+
+    /// The color for a value somewhere between `min` and `max`.
+    pub fn map(self, value: f32, min: f32, max: f32) -> Color {
+        if max == min {
+            return self.at(0.0);
+        }
+        self.at((value - min) / (max - min))
+    }
+
+    /// The same map as a gradient, for things that take one like sort bars or graph vertices.
+    pub fn gradient(self) -> Result<Gradient, GrapherError> {
+        let samples: Vec<Color> = self.samples().iter().map(|&rgb| hex(rgb)).collect();
+        Gradient::even(&samples)
+    }
+}
+
+fn hex(rgb: u32) -> Color {
+    Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+const COLORBAR_TEXT_SIZE: u16 = 14;
+const COLORBAR_TICK_LENGTH: i32 = 4;
+
+/// A vertical bar showing which color stands for which value, placed in the pixels of the axis
+/// like a cursor readout and unaffected by the axis ranges.
+pub struct Colorbar {
+    colormap: Colormap,
+    range: (f32, f32),
+    position: PixelCoordinate2D,
+    width: u32,
+    height: u32,
+    ticks: usize,
+    label: Option<String>
+}
+
+impl Colorbar {
+    pub fn new(colormap: Colormap, range: (f32, f32), position: PixelCoordinate2D, width: u32, height: u32) -> Colorbar {
+        Colorbar { colormap, range, position, width, height, ticks: 5, label: None }
+    }
+
+    /// How many values are written along the bar, including both ends.
+    pub fn with_ticks(mut self, ticks: usize) -> Colorbar {
+        self.ticks = ticks;
+        self
+    }
+
+    /// A name drawn above the bar, in math markup.
+    pub fn with_label(mut self, label: &str) -> Colorbar {
+        self.label = Some(label.to_string());
+        self
+    }
+}
+
+impl DrawableAxis2D for Colorbar {
+    fn draw(&self, renderer: &mut Renderer, _axis2d: &Axis2D) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
+        let (x, y) = (self.position.x, self.position.y);
+        // Highest value at the top
+        for row in 0..self.height {
+            let t = 1.0 - row as f32 / (self.height.max(2) - 1) as f32;
+            renderer.draw_fill_rect(PixelCoordinate2D::new(x, y + row as i32), self.width, 1, self.colormap.at(t))?;
+        }
+        renderer.draw_rect(self.position, self.width, self.height, theme.foreground)?;
+
+        let right = x + self.width as i32;
+        let style = TextStyle::new(theme.text, COLORBAR_TEXT_SIZE).with_anchor(HorizontalAnchor::Left, VerticalAnchor::Middle);
+        for tick in 0..self.ticks {
+            let t = if self.ticks > 1 { tick as f32 / (self.ticks - 1) as f32 } else { 0.5 };
+            let value = self.range.0 + (self.range.1 - self.range.0) * t;
+            let tick_y = y + ((1.0 - t) * (self.height as f32 - 1.0)).round() as i32;
+            renderer.draw_line(
+                PixelCoordinate2D::new(right, tick_y),
+                PixelCoordinate2D::new(right + COLORBAR_TICK_LENGTH, tick_y),
+                theme.foreground
+            )?;
+            renderer.draw_text_styled(
+                // Adding zero turns a rounded -0 into 0
+                &format!("{}", (value * 100.0).round() / 100.0 + 0.0),
+                PixelCoordinate2D::new(right + COLORBAR_TICK_LENGTH * 2, tick_y),
+                &style
+            )?;
+        }
+        if let Some(label) = &self.label {
+            renderer.draw_text_styled(
+                label,
+                PixelCoordinate2D::new(x + self.width as i32 / 2, y - COLORBAR_TICK_LENGTH),
+                &TextStyle::new(theme.text, COLORBAR_TEXT_SIZE).with_anchor(HorizontalAnchor::Center, VerticalAnchor::Bottom).with_math()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self, _x: f32, _y: f32) -> Option<(f32, f32)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Colormap; 6] = [Colormap::Viridis, Colormap::Magma, Colormap::Plasma, Colormap::Cividis, Colormap::Coolwarm, Colormap::Grayscale];
+
+    #[test]
+    fn maps_end_on_their_first_and_last_samples() {
+        assert_eq!(Colormap::Viridis.at(0.0), Color::RGB(0x44, 0x01, 0x54));
+        assert_eq!(Colormap::Viridis.at(1.0), Color::RGB(0xfd, 0xe7, 0x25));
+        for colormap in ALL {
+            let samples = colormap.samples();
+            assert_eq!(colormap.at(0.0), hex(samples[0]), "{:?}", colormap);
+            assert_eq!(colormap.at(1.0), hex(samples[samples.len() - 1]), "{:?}", colormap);
+        }
+    }
+
+    #[test]
+    fn values_outside_the_map_are_clamped() {
+        for colormap in ALL {
+            assert_eq!(colormap.at(-0.5), colormap.at(0.0));
+            assert_eq!(colormap.at(3.0), colormap.at(1.0));
+            assert_eq!(colormap.at(f32::NAN), colormap.at(0.0));
+            assert_eq!(colormap.at(f32::INFINITY), colormap.at(1.0));
+        }
+        assert_eq!(Colormap::Viridis.map(-20.0, 0.0, 10.0), Colormap::Viridis.at(0.0));
+        assert_eq!(Colormap::Viridis.map(20.0, 0.0, 10.0), Colormap::Viridis.at(1.0));
+        assert_eq!(Colormap::Viridis.map(5.0, 5.0, 5.0), Colormap::Viridis.at(0.0));
+    }
+
+    #[test]
+    fn blends_between_samples() {
+        assert_eq!(Colormap::Grayscale.at(0.5), Color::RGB(128, 128, 128));
+        assert_eq!(Colormap::Grayscale.map(7.5, 5.0, 15.0), Color::RGB(64, 64, 64));
+    }
+
+    #[test]
+    fn coolwarm_is_neutral_in_the_middle() {
+        assert_eq!(Colormap::Coolwarm.at(0.5), Color::RGB(0xdd, 0xdc, 0xdc));
+        assert_eq!(Colormap::Coolwarm.map(0.0, -3.0, 3.0), Color::RGB(0xdd, 0xdc, 0xdc));
+        let (cool, warm) = (Colormap::Coolwarm.at(0.0), Colormap::Coolwarm.at(1.0));
+        assert!(cool.b > cool.r && warm.r > warm.b);
+    }
+
+    #[test]
+    fn categorical_palettes_wrap_around() {
+        assert_eq!(categorical(&OKABE_ITO, 0), Some(OKABE_ITO[0]));
+        assert_eq!(categorical(&OKABE_ITO, 8), Some(OKABE_ITO[0]));
+        assert_eq!(categorical(&TOL_BRIGHT, 9), Some(TOL_BRIGHT[2]));
+        assert_eq!(categorical(&[], 3), None);
+    }
+}
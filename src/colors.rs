@@ -1,3 +1,6 @@
+use sdl2::pixels::Color;
+use crate::error::GrapherError;
+
 pub(crate) const WHITE: sdl2::pixels::Color = sdl2::pixels::Color::RGB(255, 255, 255);
 pub(crate) const BLACK: sdl2::pixels::Color = sdl2::pixels::Color::RGB(0, 0, 0);
 pub(crate) const RED: sdl2::pixels::Color = sdl2::pixels::Color::RGB(255, 0, 0);
@@ -22,3 +25,410 @@ pub(crate) const FOREST_GREEN: sdl2::pixels::Color = sdl2::pixels::Color::RGB(34
 pub(crate) const MIDNIGHT_BLUE: sdl2::pixels::Color = sdl2::pixels::Color::RGB(25, 25, 112);
 pub(crate) const INDIGO: sdl2::pixels::Color = sdl2::pixels::Color::RGB(75, 0, 130);
 pub(crate) const LIME: sdl2::pixels::Color = sdl2::pixels::Color::RGB(191, 255, 0);
+
+/// The CSS named colors sorted by name, so they can be binary searched.
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// Reads a color the way CSS writes it: `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)`, `rgba(r, g, b, a)`
+/// with alpha from 0 to 1, or one of the named colors. Case and surrounding spaces are ignored.
+pub fn parse_color(text: &str) -> Result<Color, GrapherError> {
+    let text = text.trim().to_ascii_lowercase();
+    let invalid = || GrapherError::Parse(format!("Can not read {:?} as a color", text));
+    if let Some(hex) = text.strip_prefix('#') {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>().ok_or_else(invalid)?;
+        return match digits[..] {
+            [r, g, b] => Ok(Color::RGB(r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Color::RGB(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => Ok(Color::RGBA(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2)),
+            _ => Err(invalid())
+        };
+    }
+    if let Some(arguments) = text.strip_prefix("rgba(").or_else(|| text.strip_prefix("rgb(")) {
+        let arguments = arguments.strip_suffix(')').ok_or_else(invalid)?;
+        let parts: Vec<&str> = arguments.split(',').map(str::trim).collect();
+        let channel = |part: &str| -> Result<u8, GrapherError> {
+            let value = match part.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().map(|percent| percent * 2.55),
+                None => part.parse::<f32>()
+            }.map_err(|_| invalid())?;
+            Ok(value.round().clamp(0.0, 255.0) as u8)
+        };
+        return match parts[..] {
+            [r, g, b] => Ok(Color::RGB(channel(r)?, channel(g)?, channel(b)?)),
+            [r, g, b, a] => {
+                let alpha = a.parse::<f32>().map_err(|_| invalid())?;
+                Ok(Color::RGBA(channel(r)?, channel(g)?, channel(b)?, (alpha.clamp(0.0, 1.0) * 255.0).round() as u8))
+            }
+            _ => Err(invalid())
+        };
+    }
+    if text == "transparent" {
+        return Ok(Color::RGBA(0, 0, 0, 0));
+    }
+    let index = NAMED_COLORS.binary_search_by(|(name, _)| (*name).cmp(text.as_str())).map_err(|_| invalid())?;
+    let rgb = NAMED_COLORS[index].1;
+    Ok(Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// Writes a color as `#rrggbb`, or `#rrggbbaa` when it is not opaque.
+pub fn to_hex(color: Color) -> String {
+    if color.a == 255 {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", color.r, color.g, color.b, color.a)
+    }
+}
+
+pub fn with_alpha(color: Color, alpha: u8) -> Color {
+    Color::RGBA(color.r, color.g, color.b, alpha)
+}
+
+/// The hue in degrees from 0 to 360 and the largest and smallest channel from 0 to 1, which HSL and HSV share.
+fn hue_and_range(color: Color) -> (f32, f32, f32) {
+    let (r, g, b) = (color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, max, min)
+}
+
+/// Builds a color from a hue in degrees, the chroma and the amount added to every channel, all channels from 0 to 1.
+fn from_hue(hue: f32, chroma: f32, offset: f32, alpha: u8) -> Color {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x)
+    };
+    let channel = |value: f32| ((value + offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::RGBA(channel(r), channel(g), channel(b), alpha)
+}
+
+/// Hue in degrees, saturation and lightness from 0 to 1.
+pub fn to_hsl(color: Color) -> (f32, f32, f32) {
+    let (hue, max, min) = hue_and_range(color);
+    let lightness = (max + min) / 2.0;
+    let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+    (hue, saturation, lightness)
+}
+
+pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    from_hue(hue, chroma, lightness - chroma / 2.0, 255)
+}
+
+/// Hue in degrees, saturation and value from 0 to 1.
+pub fn to_hsv(color: Color) -> (f32, f32, f32) {
+    let (hue, max, min) = hue_and_range(color);
+    let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+    (hue, saturation, max)
+}
+
+pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+    let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+    let chroma = value * saturation;
+    from_hue(hue, chroma, value - chroma, 255)
+}
+
+/// Mixes two colors, alpha included, `t` of 0 gives `from` and 1 gives `to`.
+pub fn lerp(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::RGBA(mix(from.r, to.r), mix(from.g, to.g), mix(from.b, to.b), mix(from.a, to.a))
+}
+
+/// Colors placed along a line from 0 to 1 and blended in between, for coloring things by a value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>
+}
+
+impl Gradient {
+    /// Stops are sorted by position, values before the first or after the last stop take its color.
+    pub fn new(stops: &[(f32, Color)]) -> Result<Gradient, GrapherError> {
+        if stops.is_empty() {
+            return Err(GrapherError::InvalidData("A gradient needs at least one color".to_string()));
+        }
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Gradient { stops })
+    }
+
+    /// Evenly spaced colors from 0 to 1.
+    pub fn even(colors: &[Color]) -> Result<Gradient, GrapherError> {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        let stops: Vec<(f32, Color)> = colors.iter().enumerate().map(|(i, &color)| (i as f32 / last, color)).collect();
+        Gradient::new(&stops)
+    }
+
+    pub fn at(&self, t: f32) -> Color {
+        let first = self.stops[0];
+        let last = self.stops[self.stops.len() - 1];
+        if t.is_nan() || t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let end = self.stops.iter().position(|stop| stop.0 >= t).unwrap_or(self.stops.len() - 1);
+        let (start, end) = (self.stops[end - 1], self.stops[end]);
+        lerp(start.1, end.1, (t - start.0) / (end.0 - start.0))
+    }
+
+    /// The color for a value somewhere between `min` and `max`.
+    pub fn map(&self, value: f32, min: f32, max: f32) -> Color {
+        if max == min {
+            return self.at(0.0);
+        }
+        self.at((value - min) / (max - min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(parse_color("#ff8000").unwrap(), Color::RGB(255, 128, 0));
+        assert_eq!(parse_color("#F80").unwrap(), Color::RGB(255, 136, 0));
+        assert_eq!(parse_color("#10203040").unwrap(), Color::RGBA(16, 32, 48, 64));
+        assert_eq!(parse_color("  #ABCDEF ").unwrap(), Color::RGB(171, 205, 239));
+    }
+
+    #[test]
+    fn parses_rgb_functions() {
+        assert_eq!(parse_color("rgb(220, 20, 60)").unwrap(), Color::RGB(220, 20, 60));
+        assert_eq!(parse_color("rgb(100%, 0%, 50%)").unwrap(), Color::RGB(255, 0, 128));
+        assert_eq!(parse_color("rgba(0, 0, 255, 0.5)").unwrap(), Color::RGBA(0, 0, 255, 128));
+        assert_eq!(parse_color("RGBA(1,2,3,1)").unwrap(), Color::RGBA(1, 2, 3, 255));
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse_color("steelblue").unwrap(), Color::RGB(70, 130, 180));
+        assert_eq!(parse_color("RebeccaPurple").unwrap(), Color::RGB(102, 51, 153));
+        assert_eq!(parse_color("transparent").unwrap(), Color::RGBA(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn names_are_sorted_for_the_binary_search() {
+        assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        for text in ["", "#", "#12", "#12345", "#ggg", "rgb(1, 2)", "rgb(1, 2, 3", "rgba(1, 2, 3, x)", "rgb(a, b, c)", "notacolor"] {
+            assert!(matches!(parse_color(text), Err(GrapherError::Parse(_))), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn writes_hex() {
+        assert_eq!(to_hex(Color::RGB(255, 128, 0)), "#ff8000");
+        assert_eq!(to_hex(Color::RGBA(1, 2, 3, 4)), "#01020304");
+    }
+
+    #[test]
+    fn hsl_and_hsv_round_trip() {
+        let colors = [
+            Color::RGB(255, 0, 0), Color::RGB(0, 255, 0), Color::RGB(0, 0, 255),
+            Color::RGB(0, 0, 0), Color::RGB(128, 128, 128), Color::RGB(255, 255, 255),
+            Color::RGB(70, 130, 180)
+        ];
+        for color in colors {
+            let (h, s, l) = to_hsl(color);
+            assert_eq!(from_hsl(h, s, l), color, "HSL {:?}", (h, s, l));
+            let (h, s, v) = to_hsv(color);
+            assert_eq!(from_hsv(h, s, v), color, "HSV {:?}", (h, s, v));
+        }
+    }
+
+    #[test]
+    fn hsl_of_primaries_and_grays() {
+        assert_eq!(to_hsl(Color::RGB(255, 0, 0)), (0.0, 1.0, 0.5));
+        assert_eq!(to_hsl(Color::RGB(0, 255, 0)), (120.0, 1.0, 0.5));
+        assert_eq!(to_hsv(Color::RGB(0, 0, 255)), (240.0, 1.0, 1.0));
+        let (_, saturation, _) = to_hsl(Color::RGB(128, 128, 128));
+        assert_eq!(saturation, 0.0);
+        assert_eq!(to_hsv(Color::RGB(0, 0, 0)), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn gradient_clamps_outside_its_stops() {
+        let gradient = Gradient::even(&[Color::RGB(0, 0, 0), Color::RGB(200, 100, 0)]).unwrap();
+        assert_eq!(gradient.at(-1.0), Color::RGB(0, 0, 0));
+        assert_eq!(gradient.at(2.0), Color::RGB(200, 100, 0));
+        assert_eq!(gradient.at(f32::NAN), Color::RGB(0, 0, 0));
+        assert_eq!(gradient.at(0.5), Color::RGB(100, 50, 0));
+        assert_eq!(gradient.map(15.0, 10.0, 20.0), Color::RGB(100, 50, 0));
+    }
+
+    #[test]
+    fn gradient_needs_a_color() {
+        assert!(matches!(Gradient::new(&[]), Err(GrapherError::InvalidData(_))));
+    }
+}
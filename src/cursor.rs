@@ -1,10 +1,10 @@
 use sdl2::{EventPump, Sdl};
 use crate::axis::{Axis2D, DrawableAxis2D};
-use crate::colors::{GRAY, LIGHT_GRAY, WHITE};
 use crate::config::{WINDOW_HEIGHT, WINDOW_WIDTH};
 use crate::coordinate::{CartesianCoordinate2D, PixelCoordinate2D};
 use crate::function::UnaryFunction;
 use crate::renderer::{Drawable, Renderer};
+use crate::error::GrapherError;
 
 pub struct Cursor;
 
@@ -20,9 +20,9 @@ impl Cursor {
 }
 
 impl Drawable for Cursor {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
         // if mouse is not in window, do not draw
-        if !renderer.sdl.mouse().focused_window_id().is_some_and(|id| id == renderer.canvas.window().id()) {
+        if !renderer.sdl.mouse().focused_window_id().is_some_and(|id| Some(id) == renderer.window_id()) {
             return Ok(());
         }
         // if mouse not hidden hide it
@@ -30,19 +30,22 @@ impl Drawable for Cursor {
             renderer.sdl.mouse().show_cursor(false);
         }
         let position = self.mouse_position(&renderer.event_pump);
-        renderer.draw_dashed_line(
+        let color = renderer.theme().grid;
+        renderer.draw_dashed_line_anchored(
             PixelCoordinate2D::new(position.x, 0),
             PixelCoordinate2D::new(position.x, WINDOW_HEIGHT as i32),
-            LIGHT_GRAY,
+            color,
             15,
-            20
+            20,
+            position
         )?;
-        renderer.draw_dashed_line(
+        renderer.draw_dashed_line_anchored(
             PixelCoordinate2D::new(0, position.y),
             PixelCoordinate2D::new(WINDOW_WIDTH as i32, position.y),
-            LIGHT_GRAY,
+            color,
             15,
-            20
+            20,
+            position
         )?;
         Ok(())
     }
@@ -66,21 +69,21 @@ impl CursorReadout {
 }
 
 impl DrawableAxis2D for CursorReadout {
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
         let value = PixelCoordinate2D::new(
             renderer.event_pump.mouse_state().x(),
             renderer.event_pump.mouse_state().y()
         );
         match self.readout_type {
             ReadoutType::Cartesian => {
-                let value = axis2d.to_cartesian(value);
+                let value = axis2d.to_cartesian(axis2d.window_to_local(value));
                 let highest = axis2d.highest_object(value.x);
                 let y = highest.unwrap_or(value.y);
                 let value = CartesianCoordinate2D::new(value.x, y);
                 renderer.draw_text(
                     &format!("({:.2}, {:.2})", value.x, value.y),
                     self.position,
-                    WHITE,
+                    renderer.theme().text,
                     20
                 )?;
             },
@@ -88,7 +91,7 @@ impl DrawableAxis2D for CursorReadout {
                 renderer.draw_text(
                     &format!("({}, {})", value.x, value.y),
                     self.position,
-                    GRAY,
+                    renderer.theme().grid,
                     20
                 )?;
             }
@@ -105,33 +108,37 @@ impl DrawableAxis2D for CursorReadout {
 pub type SnappingCursor = Cursor;
 
 impl DrawableAxis2D for SnappingCursor {
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
         // if mouse is not in window, do not draw
-        if !renderer.sdl.mouse().focused_window_id().is_some_and(|id| id == renderer.canvas.window().id()) {
+        if !renderer.sdl.mouse().focused_window_id().is_some_and(|id| Some(id) == renderer.window_id()) {
             return Ok(());
         }
         // if mouse not hidden hide it
         if renderer.sdl.mouse().is_cursor_showing() {
             renderer.sdl.mouse().show_cursor(false);
         }
-        let position = axis2d.to_cartesian(self.mouse_position(&renderer.event_pump));
+        let position = axis2d.to_cartesian(axis2d.window_to_local(self.mouse_position(&renderer.event_pump)));
         // snap mouse y to the axis highest
         let highest = axis2d.highest_object(position.x);
         let y = highest.unwrap_or(position.y);
         let position = axis2d.to_pixel(CartesianCoordinate2D::new(position.x, y));
-        renderer.draw_dashed_line(
+        let (width, height) = axis2d.size();
+        let color = renderer.theme().grid;
+        renderer.draw_dashed_line_anchored(
             PixelCoordinate2D::new(position.x, 0),
-            PixelCoordinate2D::new(position.x, WINDOW_HEIGHT as i32),
-            LIGHT_GRAY,
+            PixelCoordinate2D::new(position.x, height as i32),
+            color,
             15,
-            20
+            20,
+            position
         )?;
-        renderer.draw_dashed_line(
+        renderer.draw_dashed_line_anchored(
             PixelCoordinate2D::new(0, position.y),
-            PixelCoordinate2D::new(WINDOW_WIDTH as i32, position.y),
-            LIGHT_GRAY,
+            PixelCoordinate2D::new(width as i32, position.y),
+            color,
             15,
-            20
+            20,
+            position
         )?;
         Ok(())
     }
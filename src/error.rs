@@ -0,0 +1,92 @@
+use std::fmt;
+
+/// Everything that can go wrong while drawing, so an application embedding the grapher can tell
+/// a broken window from a bad label and decide for itself what to do.
+#[derive(Debug)]
+pub enum GrapherError {
+    /// SDL refused to create, draw to or read from a window, canvas or texture
+    Sdl(String),
+    /// A font could not be loaded, measured or rendered
+    Font(String),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// Text that could not be understood, like unbalanced math markup
+    Parse(String),
+    /// A request that does not fit the current state, like an edge from a vertex that does not exist
+    InvalidData(String)
+}
+
+impl fmt::Display for GrapherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrapherError::Sdl(message) => write!(f, "SDL error: {}", message),
+            GrapherError::Font(message) => write!(f, "Font error: {}", message),
+            GrapherError::Io(error) => write!(f, "IO error: {}", error),
+            GrapherError::Parse(message) => write!(f, "Parse error: {}", message),
+            GrapherError::InvalidData(message) => write!(f, "Invalid data: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for GrapherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GrapherError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for GrapherError {
+    fn from(error: std::io::Error) -> Self {
+        GrapherError::Io(error)
+    }
+}
+
+impl From<png::EncodingError> for GrapherError {
+    fn from(error: png::EncodingError) -> Self {
+        match error {
+            png::EncodingError::IoError(error) => GrapherError::Io(error),
+            error => GrapherError::InvalidData(error.to_string())
+        }
+    }
+}
+
+impl From<gif::EncodingError> for GrapherError {
+    fn from(error: gif::EncodingError) -> Self {
+        match error {
+            gif::EncodingError::Io(error) => GrapherError::Io(error),
+            error => GrapherError::InvalidData(error.to_string())
+        }
+    }
+}
+
+impl From<sdl2::video::WindowBuildError> for GrapherError {
+    fn from(error: sdl2::video::WindowBuildError) -> Self {
+        GrapherError::Sdl(error.to_string())
+    }
+}
+
+impl From<sdl2::IntegerOrSdlError> for GrapherError {
+    fn from(error: sdl2::IntegerOrSdlError) -> Self {
+        GrapherError::Sdl(error.to_string())
+    }
+}
+
+impl From<sdl2::render::TextureValueError> for GrapherError {
+    fn from(error: sdl2::render::TextureValueError) -> Self {
+        GrapherError::Sdl(error.to_string())
+    }
+}
+
+impl From<sdl2::ttf::FontError> for GrapherError {
+    fn from(error: sdl2::ttf::FontError) -> Self {
+        GrapherError::Font(error.to_string())
+    }
+}
+
+impl From<sdl2::ttf::InitError> for GrapherError {
+    fn from(error: sdl2::ttf::InitError) -> Self {
+        GrapherError::Font(error.to_string())
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use crate::backend::Backend;
+use crate::pdf::PdfBackend;
+use crate::renderer::{Drawable, Renderer};
+use crate::svg::SvgBackend;
+use crate::error::GrapherError;
+
+/// Writes tightly packed RGBA pixels, as returned by `Renderer::read_pixels`, to a PNG file.
+pub fn write_png<P: AsRef<Path>>(path: P, width: u32, height: u32, pixels: &[u8]) -> Result<(), GrapherError> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish().map_err(GrapherError::from)
+}
+
+fn downcast<B: Backend + 'static>(backend: Box<dyn Backend>) -> Result<Box<B>, GrapherError> {
+    backend.into_any().downcast::<B>().map_err(|_| GrapherError::InvalidData("Backend was swapped while drawing".to_string()))
+}
+
+impl Renderer {
+    /// Saves whatever has been drawn so far this frame.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), GrapherError> {
+        let (width, height) = self.size()?;
+        write_png(path, width, height, &self.read_pixels()?)
+    }
+
+    /// Renders a single object on its own at the given resolution and saves it,
+    /// the current frame is left untouched.
+    pub fn export_png<P: AsRef<Path>>(&mut self, object: &dyn Drawable, path: P, width: u32, height: u32) -> Result<(), GrapherError> {
+        let pixels = self.render_offscreen(object, width, height)?;
+        write_png(path, width, height, &pixels)
+    }
+
+    /// Draws a single object as vector graphics and saves it as an SVG file the same size as this renderer.
+    pub fn export_svg<P: AsRef<Path>>(&mut self, object: &dyn Drawable, path: P) -> Result<(), GrapherError> {
+        let (width, height) = self.size()?;
+        let backend = self.draw_with_backend(Box::new(SvgBackend::new(width, height)), object)?;
+        let svg = downcast::<SvgBackend>(backend)?;
+        std::fs::write(path, svg.to_svg()).map_err(GrapherError::from)
+    }
+
+    /// Draws a single object into a one page PDF the same size as this renderer.
+    pub fn export_pdf<P: AsRef<Path>>(&mut self, object: &dyn Drawable, path: P) -> Result<(), GrapherError> {
+        self.export_pdf_pages(path, 1, |renderer, _| object.draw(renderer))
+    }
+
+    /// Builds a PDF with one page per frame, `draw_page` is called with the page index after the page is cleared.
+    /// Useful for stepping through something like a sort and keeping every step.
+    pub fn export_pdf_pages<P, F>(&mut self, path: P, pages: usize, mut draw_page: F) -> Result<(), GrapherError>
+    where P: AsRef<Path>, F: FnMut(&mut Renderer, usize) -> Result<(), GrapherError> {
+        let (width, height) = self.size()?;
+        let backend = self.with_backend(Box::new(PdfBackend::new(width, height)?), |renderer| {
+            for page in 0..pages {
+                renderer.clear();
+                draw_page(renderer, page)?;
+                renderer.present();
+            }
+            Ok(())
+        })?;
+        let pdf = downcast::<PdfBackend>(backend)?;
+        std::fs::write(path, pdf.to_pdf()).map_err(GrapherError::from)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use sdl2::pixels::Color;
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use crate::error::GrapherError;
+
+pub(crate) const FONT_DATA: &[u8] = include_bytes!("../assets/JetBrainsMono.ttf");
+/// Used for any text with characters JetBrainsMono does not have, like most of the Greek alphabet.
+/// DejaVu fonts are under the Bitstream Vera and Arev licenses, see `assets/DejaVuSansMono-LICENSE.txt`.
+pub(crate) const FALLBACK_FONT_DATA: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+const MAX_CACHED_TEXT: usize = 2048;
+// Which glyphs a font has does not depend on the size, so coverage is always checked at this one
+const FALLBACK_PROBE_SIZE: u16 = 12;
+
+static TTF_CONTEXT: OnceLock<Sdl2TtfContext> = OnceLock::new();
+
+fn ttf_context() -> Result<&'static Sdl2TtfContext, GrapherError> {
+    if let Some(context) = TTF_CONTEXT.get() {
+        return Ok(context);
+    }
+    let context = sdl2::ttf::init()?;
+    Ok(TTF_CONTEXT.get_or_init(|| context))
+}
+
+/// Keeps fonts loaded per size and the rendered text surfaces around between frames,
+/// so drawing the same label again is only a texture upload.
+pub struct FontManager {
+    fonts: HashMap<(u16, bool), Font<'static, 'static>>,
+    text: HashMap<(String, u16, Color), Surface<'static>>
+}
+
+impl FontManager {
+    pub fn new() -> FontManager {
+        FontManager { fonts: HashMap::new(), text: HashMap::new() }
+    }
+
+    pub fn font(&mut self, size: u16) -> Result<&Font<'static, 'static>, GrapherError> {
+        self.load(size, false)
+    }
+
+    fn load(&mut self, size: u16, fallback: bool) -> Result<&Font<'static, 'static>, GrapherError> {
+        match self.fonts.entry((size, fallback)) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let data = if fallback { FALLBACK_FONT_DATA } else { FONT_DATA };
+                let font = ttf_context()?.load_font_from_rwops(RWops::from_bytes(data).map_err(GrapherError::Sdl)?, size).map_err(GrapherError::Sdl)?;
+                Ok(entry.insert(font))
+            }
+        }
+    }
+
+    /// Whether JetBrainsMono has every character of the text, otherwise it is drawn with the fallback font.
+    pub fn covers(&mut self, text: &str) -> Result<bool, GrapherError> {
+        let font = self.font(FALLBACK_PROBE_SIZE)?;
+        Ok(text.chars().all(|c| c.is_control() || font.find_glyph(c).is_some()))
+    }
+
+    /// The font the text is drawn with, a whole string always uses the same one so it lines up with itself.
+    fn font_for(&mut self, text: &str, size: u16) -> Result<&Font<'static, 'static>, GrapherError> {
+        let fallback = !self.covers(text)?;
+        self.load(size, fallback)
+    }
+
+    /// The rendered text, only rasterized the first time it is asked for.
+    pub fn render(&mut self, text: &str, size: u16, color: Color) -> Result<&Surface<'static>, GrapherError> {
+        // Rendered opaque, translucency is applied when the text is drawn
+        let color = Color::RGB(color.r, color.g, color.b);
+        let key = (text.to_string(), size, color);
+        if !self.text.contains_key(&key) {
+            // Labels that change every frame (readouts, counters) would otherwise grow the cache forever
+            if self.text.len() >= MAX_CACHED_TEXT {
+                self.text.clear();
+            }
+            let surface = self.font_for(text, size)?.render(text).blended(color)?;
+            self.text.insert(key.clone(), surface);
+        }
+        Ok(&self.text[&key])
+    }
+
+    /// The width and height the text takes up when drawn.
+    pub fn size_of(&mut self, text: &str, size: u16) -> Result<(u32, u32), GrapherError> {
+        Ok(self.font_for(text, size)?.size_of(text)?)
+    }
+
+    /// Distance from the top of a line of text to its baseline.
+    pub fn ascent(&mut self, size: u16) -> Result<i32, GrapherError> {
+        Ok(self.font(size)?.ascent())
+    }
+
+    /// Like `ascent`, for whichever font the text is drawn with.
+    pub fn text_ascent(&mut self, text: &str, size: u16) -> Result<i32, GrapherError> {
+        Ok(self.font_for(text, size)?.ascent())
+    }
+
+    /// Distance from the baseline to the bottom of a line of text.
+    pub fn descent(&mut self, size: u16) -> Result<i32, GrapherError> {
+        Ok(-self.font(size)?.descent())
+    }
+
+    /// Distance from the top of one line of text to the top of the next.
+    pub fn line_height(&mut self, size: u16) -> Result<i32, GrapherError> {
+        Ok(self.font(size)?.recommended_line_spacing())
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.text.clear();
+    }
+}
+
+impl Default for FontManager {
+    fn default() -> Self {
+        FontManager::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use super::*;
+    use crate::colors::RED;
+    use crate::coordinate::PixelCoordinate2D;
+    use crate::renderer::tests::with_headless_renderer;
+    use crate::renderer::Renderer;
+
+    #[test]
+    fn rendering_the_same_text_again_hits_the_cache() {
+        let mut fonts = FontManager::new();
+        let first = fonts.render("1.5", 14, RED).unwrap().raw();
+        let second = fonts.render("1.5", 14, RED).unwrap().raw();
+        assert_eq!(first, second);
+        assert_eq!(fonts.text.len(), 1);
+
+        // Only the color channels are part of the key, text is rendered opaque
+        let translucent = fonts.render("1.5", 14, Color::RGBA(RED.r, RED.g, RED.b, 100)).unwrap().raw();
+        assert_eq!(first, translucent);
+        fonts.render("1.5", 16, RED).unwrap();
+        assert_eq!(fonts.text.len(), 2);
+    }
+
+    #[test]
+    fn the_cache_is_dropped_once_full() {
+        let mut fonts = FontManager::new();
+        for i in 0..MAX_CACHED_TEXT {
+            fonts.render(&i.to_string(), 10, RED).unwrap();
+        }
+        assert_eq!(fonts.text.len(), MAX_CACHED_TEXT);
+        fonts.render("one more", 10, RED).unwrap();
+        assert_eq!(fonts.text.len(), 1);
+    }
+
+    fn draw_labels(renderer: &mut Renderer, frames: usize, cached: bool) -> Duration {
+        let start = Instant::now();
+        for _ in 0..frames {
+            if !cached {
+                renderer.fonts.clear_cache();
+            }
+            renderer.clear();
+            for i in 0..300 {
+                let position = PixelCoordinate2D::new((i % 20) * 40, (i / 20) * 20);
+                renderer.draw_text(&format!("{:.1}", i as f32 * 0.5), position, RED, 12).unwrap();
+            }
+        }
+        start.elapsed()
+    }
+
+    // Run with `cargo test --release -- --ignored --nocapture label_drawing_speed`
+    #[test]
+    #[ignore]
+    fn label_drawing_speed() {
+        with_headless_renderer(800, 300, |renderer| {
+            // Loads the fonts, so neither run pays for it
+            draw_labels(renderer, 1, true);
+            let uncached = draw_labels(renderer, 20, false);
+            let cached = draw_labels(renderer, 20, true);
+            println!("300 labels over 20 frames: {:?} without the text cache, {:?} with it", uncached, cached);
+        });
+    }
+}
@@ -4,40 +4,123 @@ use crate::axis::{Axis2D, DrawableAxis2D};
 use crate::colors::{GREEN, RED};
 use crate::coordinate::CartesianCoordinate2D;
 use crate::renderer::Renderer;
+use crate::stroke::StrokeStyle;
+use crate::text::{HorizontalAnchor, TextStyle, VerticalAnchor};
+use crate::error::GrapherError;
+
+const LABEL_SIZE: u16 = 20;
+
+/// Splits the pixels of a sampled curve into the runs that can be drawn, breaking it wherever
+/// the curve is undefined. A single point left between two gaps has nothing to connect to and is dropped.
+pub(crate) fn drawable_runs(points: impl IntoIterator<Item = (f32, f32)>) -> Vec<Vec<(f32, f32)>> {
+    let points: Vec<(f32, f32)> = points.into_iter().collect();
+    points.split(|(x, y)| !x.is_finite() || !y.is_finite())
+        .filter(|run| run.len() > 1)
+        .map(|run| run.to_vec())
+        .collect()
+}
 
 pub struct UnaryFunction {
     function: Box<dyn Fn(f32) -> f32>,
-    color: Color
+    color: Color,
+    stroke: Option<StrokeStyle>,
+    fill: Option<Color>,
+    label: Option<String>
 }
 
 impl UnaryFunction {
     pub fn new(function: Box<dyn Fn(f32) -> f32>, color: Color) -> UnaryFunction {
-        UnaryFunction { function, color }
+        UnaryFunction { function, color, stroke: None, fill: None, label: None }
     }
-}
 
-impl DrawableAxis2D for UnaryFunction {
-    // TODO: Fix the stair stepping effect
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String> {
+    /// Draws the curve with a stroke style instead of a plain one pixel line, for thick or dashed curves.
+    pub fn with_stroke(mut self, stroke: StrokeStyle) -> UnaryFunction {
+        self.color = stroke.color;
+        self.stroke = Some(stroke);
+        self
+    }
+
+    /// Shades the area between the curve and the x axis, a translucent color keeps whatever is behind visible.
+    pub fn with_fill(mut self, fill: Color) -> UnaryFunction {
+        self.fill = Some(fill);
+        self
+    }
+
+    fn pixel_runs(&self, axis2d: &Axis2D) -> Vec<Vec<(f32, f32)>> {
+        drawable_runs(axis2d.x_samples().into_iter().map(|x| {
+            axis2d.to_pixel_precise(CartesianCoordinate2D::new(x, (self.function)(x)))
+        }))
+    }
+
+    fn draw_fill(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
+        let Some(fill) = self.fill else {
+            return Ok(());
+        };
+        let axis_y = axis2d.to_pixel_precise(CartesianCoordinate2D::new(0.0, 0.0)).1;
+        // A log y axis has no zero, so fill down to the bottom instead
+        let axis_y = if axis_y.is_finite() { axis_y } else { axis2d.size().1 as f32 };
+        for mut area in self.pixel_runs(axis2d) {
+            area.push((area[area.len() - 1].0, axis_y));
+            area.push((area[0].0, axis_y));
+            renderer.fill_polygon(&area, fill)?;
+        }
+        Ok(())
+    }
+
+    /// A legend drawn at the right end of the curve, in math markup so things like `x^2` work.
+    pub fn with_label(mut self, label: &str) -> UnaryFunction {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    fn draw_label(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
+        let Some(label) = &self.label else {
+            return Ok(());
+        };
         let range = axis2d.range();
-        let steps = axis2d.steps();
-        let step_size = axis2d.step_size();
-        let mut last = axis2d.to_pixel(
-            CartesianCoordinate2D::new(range.0.0, (self.function)(range.0.0))
-        );
-        for i in  0..steps.0 {
-            let x = range.0.0 + i as f32 * step_size;
-            let y = (self.function)(x);
-            let current = axis2d.to_pixel(CartesianCoordinate2D::new(x, y));
-            // println!("({}, {})", x, y);
-            renderer.draw_line(last, current, self.color)?;
-            // renderer.draw_dot(last, GREEN)?;
-            last = current;
+        // The last point of the curve that is actually on screen
+        let end = axis2d.x_samples().into_iter().rev()
+            .map(|x| CartesianCoordinate2D::new(x, (self.function)(x)))
+            .find(|point| point.y.is_finite() && point.y >= range.1.0 && point.y <= range.1.1);
+        if let Some(end) = end {
+            renderer.draw_text_styled(
+                label,
+                axis2d.to_pixel(end),
+                &TextStyle::new(self.color, LABEL_SIZE).with_anchor(HorizontalAnchor::Right, VerticalAnchor::Bottom).with_math()
+            )?;
         }
         Ok(())
     }
+}
+
+impl DrawableAxis2D for UnaryFunction {
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
+        self.draw_fill(renderer, axis2d)?;
+        for run in self.pixel_runs(axis2d) {
+            if let Some(stroke) = &self.stroke {
+                renderer.stroke_polyline(&run, stroke)?;
+                continue;
+            }
+            for segment in run.windows(2) {
+                renderer.draw_line_precise(segment[0], segment[1], self.color)?;
+            }
+        }
+        self.draw_label(renderer, axis2d)
+    }
 
     fn evaluate(&self, x: f32, y: f32) -> Option<(f32, f32)> {
         Some((0.0, (self.function)(x)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_break_where_undefined() {
+        let points = [(0.0, 0.0), (1.0, 1.0), (2.0, f32::NAN), (3.0, 3.0), (4.0, f32::INFINITY), (5.0, 5.0), (6.0, 6.0)];
+        assert_eq!(drawable_runs(points), vec![vec![(0.0, 0.0), (1.0, 1.0)], vec![(5.0, 5.0), (6.0, 6.0)]]);
+        assert!(drawable_runs([(0.0, f32::NAN)]).is_empty());
+    }
+}
@@ -1,21 +1,32 @@
 use std::cmp::PartialEq;
+use std::collections::HashMap;
 use rand::Rng;
-use crate::colors::{BLUE, CYAN, DARK_YELLOW, GRAY, LIGHT_GRAY, WHITE, YELLOW};
+use crate::colors::Gradient;
 use crate::coordinate::PixelCoordinate2D;
+use crate::path::Path;
 use crate::renderer::{Drawable, Renderer};
+use crate::stroke::StrokeStyle;
+use crate::text::{HorizontalAnchor, TextStyle, VerticalAnchor};
+use crate::error::GrapherError;
 
 const RADIUS: u32 = 20;
 const LINE_LENGTH: i32 = 60;
 const FONT_SIZE: u16 = 25;
 const ANGLE: f64 = 1.0;
+// How far the middle of an edge bows out to the side, as a fraction of its length
+const TREE_EDGE_BEND: f32 = 0.1;
+const CROSS_EDGE_BEND: f32 = 0.3;
 
 pub struct Graph {
-    pub objects: Vec<(Vertex, Edge)>
+    pub objects: Vec<(Vertex, Edge)>,
+    degree_colors: Option<Gradient>
 }
 
 pub struct Edge {
     pub start: PixelCoordinate2D,
     pub end: PixelCoordinate2D,
+    /// The value of the vertex the edge leaves from
+    source: i32,
     tree_type: bool
 }
 
@@ -40,13 +51,34 @@ impl Graph {
             Edge {
                 start: PixelCoordinate2D::center(),
                 end: PixelCoordinate2D::center(),
+                source,
                 tree_type: false
             }
         ));
-        Graph { objects }
+        Graph { objects, degree_colors: None }
     }
 
-    pub fn fill_random(&mut self, node_count: i32) {
+    /// Colors vertices by how many edges touch them, from the lowest degree in the graph to the highest.
+    pub fn with_degree_colors(mut self, gradient: Gradient) -> Graph {
+        self.degree_colors = Some(gradient);
+        self
+    }
+
+    /// The number of edges touching each vertex, by value.
+    fn degrees(&self) -> HashMap<i32, u32> {
+        let mut degrees = HashMap::new();
+        for (vertex, edge) in &self.objects {
+            degrees.entry(vertex.value).or_insert(0);
+            // Every entry but the origin's carries the edge that leads into its vertex
+            if edge.start != edge.end {
+                *degrees.entry(vertex.value).or_insert(0) += 1;
+                *degrees.entry(edge.source).or_insert(0) += 1;
+            }
+        }
+        degrees
+    }
+
+    pub fn fill_random(&mut self, node_count: i32) -> Result<(), GrapherError> {
         let mut rng = rand::thread_rng();
         // for i in 1..node_count {
         //     let source = rng.gen_range(0..i);
@@ -61,12 +93,13 @@ impl Graph {
         while i < node_count {
             if let Some(vertex) = self.get_vertex(source) {
                 if vertex.children < 2 {
-                    self.add_vertex(vertex.value, i).unwrap();
+                    self.add_vertex(vertex.value, i)?;
                     i += 1;
                 }
             }
             source = rng.gen_range(0..i);
         }
+        Ok(())
     }
 
     fn get_vertex(&self, value: i32) -> Option<&Vertex> {
@@ -78,14 +111,14 @@ impl Graph {
         None
     }
 
-    pub fn add_vertex_from_list(&mut self, list: &[(i32, i32)]) -> Result<(), String> {
+    pub fn add_vertex_from_list(&mut self, list: &[(i32, i32)]) -> Result<(), GrapherError> {
         for (source, target) in list {
             self.add_vertex(*source, *target)?;
         }
         Ok(())
     }
 
-    pub fn add_vertex(&mut self, source: i32, target: i32) -> Result<(), String> {
+    pub fn add_vertex(&mut self, source: i32, target: i32) -> Result<(), GrapherError> {
         let mut source_vertex = None;
         let mut target_vertex = None;
         for (vertex, _) in &mut self.objects {
@@ -95,10 +128,9 @@ impl Graph {
                 target_vertex = Some(vertex);
             }
         }
-        if source_vertex.is_none() {
-            return Err("Source vertex not found".to_string());
-        }
-        let source_vertex = source_vertex.unwrap();
+        let Some(source_vertex) = source_vertex else {
+            return Err(GrapherError::InvalidData(format!("Source vertex {} not found", source)));
+        };
         source_vertex.children += 1;
         let source_vertex = source_vertex.clone();
         if target_vertex.is_none() {
@@ -121,6 +153,7 @@ impl Graph {
                     target_vertex.position.x - (RADIUS as f64 * angle.cos()) as i32,
                     target_vertex.position.y - (RADIUS as f64 * angle.sin()) as i32
                 ),
+                source,
                 tree_type: true
             };
             self.objects.push((
@@ -142,6 +175,7 @@ impl Graph {
                     target_vertex.position.x - (RADIUS as f64 * angle.cos()) as i32,
                     target_vertex.position.y - (RADIUS as f64 * angle.sin()) as i32
                 ),
+                source,
                 tree_type: false
             };
             self.objects.push((
@@ -153,26 +187,41 @@ impl Graph {
     }
 }
 
+impl Edge {
+    /// A quadratic curve between the ends, bowed out to the left of the direction of travel
+    /// so edges going both ways between the same vertices do not overlap.
+    fn curve(&self, bend: f32) -> Path {
+        let start = (self.start.x as f32, self.start.y as f32);
+        let end = (self.end.x as f32, self.end.y as f32);
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let control = ((start.0 + end.0) / 2.0 + dy * bend, (start.1 + end.1) / 2.0 - dx * bend);
+        Path::new().move_to(start).quad_to(control, end)
+    }
+}
+
 impl Drawable for Graph {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
+        let degrees = self.degrees();
+        let (min_degree, max_degree) = (
+            degrees.values().copied().min().unwrap_or(0),
+            degrees.values().copied().max().unwrap_or(0)
+        );
         for (vertex, edge) in &self.objects {
-            let color = if vertex.origin { CYAN } else { WHITE };
+            let color = match &self.degree_colors {
+                Some(gradient) => gradient.map(degrees[&vertex.value] as f32, min_degree as f32, max_degree as f32),
+                None if vertex.origin => theme.accent(0),
+                None => theme.foreground
+            };
             renderer.draw_circle(vertex.position, RADIUS, color)?;
             if edge.start != edge.end {
-                if edge.tree_type {
-                    renderer.draw_line(edge.start, edge.end, LIGHT_GRAY)?;
-                } else {
-                    renderer.draw_line(edge.start, edge.end, YELLOW)?;
-                }
+                let (color, bend) = if edge.tree_type { (theme.grid, TREE_EDGE_BEND) } else { (theme.accent(1), CROSS_EDGE_BEND) };
+                renderer.stroke_path(&edge.curve(bend), &StrokeStyle::new(color, 1.0))?;
             }
-            renderer.draw_text(
+            renderer.draw_text_styled(
                 &vertex.value.to_string(),
-                PixelCoordinate2D::new(
-                    vertex.position.x - FONT_SIZE as i32 / 3,
-                    vertex.position.y - FONT_SIZE as i32 / 2
-                ),
-                WHITE,
-                FONT_SIZE
+                vertex.position,
+                &TextStyle::new(theme.text, FONT_SIZE).with_anchor(HorizontalAnchor::Center, VerticalAnchor::Middle)
             )?;
         }
         Ok(())
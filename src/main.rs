@@ -8,15 +8,24 @@ use std::time::Duration;
 use crate::axis::Axis2D;
 use crate::colors::{BLUE, GREEN, RED, WHITE};
 use crate::config::{CENTER_Y, WINDOW_HEIGHT, WINDOW_WIDTH};
-use crate::coordinate::PixelCoordinate2D;
+use crate::colormap::{Colorbar, Colormap, TOL_BRIGHT};
+use crate::coordinate::{CartesianCoordinate2D, PixelCoordinate2D};
 use crate::cursor::ReadoutType;
 use crate::function::UnaryFunction;
+use crate::polar::{AngleUnit, PolarAxis, PolarFunction, RosePlot};
+use crate::recorder::Recorder;
+use crate::scale::Scale;
+use crate::renderer::Drawable;
+use crate::scene::Scene;
+use crate::theme::Theme;
+use crate::error::GrapherError;
 
 pub mod config;
 pub mod renderer;
 mod coordinate;
 mod axis;
 mod colors;
+mod colormap;
 mod function;
 mod scatter;
 mod cursor;
@@ -24,10 +33,28 @@ mod sort;
 mod audio;
 mod mohr;
 mod graph;
-
-pub fn main() -> Result<(), String> {
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
+mod export;
+mod backend;
+mod svg;
+mod pdf;
+mod font;
+mod raster;
+mod stroke;
+mod path;
+mod text;
+mod ticks;
+mod scale;
+mod math;
+mod error;
+mod recorder;
+mod theme;
+mod scene;
+mod polar;
+
+pub fn main() -> Result<(), GrapherError> {
+    env_logger::init();
+    let sdl_context = sdl2::init().map_err(GrapherError::Sdl)?;
+    let video_subsystem = sdl_context.video().map_err(GrapherError::Sdl)?;
 
     let window = video_subsystem
         .window(
@@ -38,11 +65,21 @@ pub fn main() -> Result<(), String> {
         .position_centered()
         // .vulkan()
         .opengl()
-        .build()
-        .map_err(|e| e.to_string())?;
+        .build()?;
 
     let mut renderer = renderer::Renderer::new(window, sdl_context)?;
 
+    let mut scene = Scene::new();
+    scene.add_layer("axis", 0)?;
+    scene.add_layer("mohr", 1)?;
+    scene.add_layer("graph", 2)?;
+    scene.add_layer("complexity", 3)?;
+    scene.add_layer("polar", 4)?;
+    scene.set_layer_visible("axis", false)?;
+    scene.set_layer_visible("mohr", false)?;
+    scene.set_layer_visible("complexity", false)?;
+    scene.set_layer_visible("polar", false)?;
+
     let mut axis: Axis2D;
     {
         axis = axis::Axis2D::new(
@@ -51,39 +88,131 @@ pub fn main() -> Result<(), String> {
             WINDOW_HEIGHT as f32 / 2.5,
             0.01
         );
+        axis.set_grid(true, false);
+        axis.set_interactive(true);
         axis.add_object(Box::new(cursor::SnappingCursor::new(&renderer.sdl)));
         axis.add_object(Box::new(cursor::CursorReadout::new(PixelCoordinate2D::new(WINDOW_WIDTH as i32 - 175, 0), ReadoutType::Cartesian)));
+        let samples: Vec<CartesianCoordinate2D> = (0..64)
+            .map(|i| -2.0 * std::f32::consts::PI + i as f32 * 4.0 * std::f32::consts::PI / 63.0)
+            .map(|x| CartesianCoordinate2D::new(x, x.sin()))
+            .collect();
+        let values = samples.iter().map(|sample| sample.y).collect();
+        axis.add_object(Box::new(
+            scatter::Scatter2D::new(samples, WHITE).with_radius(3.0).with_values(values, Colormap::Viridis, (-1.0, 1.0))
+        ));
+        axis.add_object(Box::new(
+            Colorbar::new(Colormap::Viridis, (-1.0, 1.0), PixelCoordinate2D::new(20, 40), 16, 200).with_label("y")
+        ));
     }
 
+    // Growth rates over six powers of ten only stay readable with both axes logarithmic
+    let mut complexity = axis::Axis2D::new(
+        PixelCoordinate2D::new(60, WINDOW_HEIGHT as i32 - 40),
+        (WINDOW_WIDTH - 100) as f32 / 6.0,
+        (WINDOW_HEIGHT - 80) as f32 / 12.0,
+        0.01
+    );
+    complexity.set_scales(Scale::Log10, Scale::Log10)?;
+    complexity.set_grid(true, false);
+    complexity.set_interactive(true);
+    complexity.set_labels("n", "operations");
+    complexity.add_objects([
+        Box::new(UnaryFunction::new(Box::new(|n| n), GREEN).with_label("n")) as Box<dyn axis::DrawableAxis2D>,
+        Box::new(UnaryFunction::new(Box::new(|n| n * n.log2().max(1.0)), BLUE).with_label("n log n")),
+        Box::new(UnaryFunction::new(Box::new(|n| n * n), RED).with_label("n^2"))
+    ]);
+
+    // A directional antenna pattern next to a rose plot of principal stress directions
+    let category = |index| colormap::categorical(&TOL_BRIGHT, index).ok_or(GrapherError::InvalidData("The palette is empty".to_string()));
+    let polar_radius = (WINDOW_WIDTH / 4).min(WINDOW_HEIGHT / 2) as f32 - 50.0;
+    let mut pattern = PolarAxis::new(PixelCoordinate2D::new(WINDOW_WIDTH as i32 / 4, CENTER_Y as i32), polar_radius, 1.0);
+    pattern.set_orientation(std::f32::consts::FRAC_PI_2, true);
+    pattern.add_objects([
+        Box::new(PolarFunction::new(Box::new(|theta: f32| (0.5 + 0.5 * theta.cos()).powi(2)), category(0)?)) as Box<dyn polar::DrawablePolar>,
+        Box::new(PolarFunction::new(Box::new(|theta: f32| (2.0 * theta).cos().abs() * 0.6), category(1)?))
+    ]);
+    let directions: Vec<f32> = [12.0f32, 18.0, 25.0, 27.0, 31.0, 33.0, 40.0, 44.0, 58.0, 95.0, 110.0, 150.0, 162.0, 171.0]
+        .iter()
+        .map(|degrees| degrees.to_radians())
+        .collect();
+    let rose = RosePlot::from_axial_angles(&directions, 18, category(2)?);
+    let mut stresses = PolarAxis::new(PixelCoordinate2D::new(3 * WINDOW_WIDTH as i32 / 4, CENTER_Y as i32), polar_radius, rose.max());
+    stresses.set_angle_unit(AngleUnit::Radians);
+    stresses.set_spokes(8);
+    stresses.add_object(Box::new(rose));
+
     let mohr = mohr::MohrsCircle::new([200.0, 100.0, 0.0], [80.0, 20.0, 0.0]);
 
-    let mut graph = graph::Graph::new(0);
+    let degree_colors = colors::Gradient::even(&[
+        colors::parse_color("steelblue")?,
+        colors::parse_color("#ffd700")?,
+        colors::parse_color("rgb(220, 20, 60)")?
+    ])?;
+    let mut graph = graph::Graph::new(0).with_degree_colors(degree_colors);
     // graph.fill_random(20);
     graph.add_vertex_from_list(&[(0,1), (0,2), (0,5), (1,4), (2,3), (3,8), (4,9), (5,6), (5,7), (6,7)])?;
 
+    scene.add("axis", Box::new(axis))?;
+    scene.add("mohr", Box::new(mohr))?;
+    scene.add("graph", Box::new(graph))?;
+    scene.add("complexity", Box::new(complexity))?;
+    scene.add("polar", Box::new(pattern))?;
+    scene.add("polar", Box::new(stresses))?;
+
+    let themes = [Theme::dark(), Theme::light(), Theme::print()];
+    let mut theme = 0;
+
     'running: loop {
+        let mut toggle_recording = false;
         for event in renderer.event_pump.poll_iter() {
+            // Interactive objects like the axes pan and zoom themselves
+            scene.handle_event(&event);
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
+                Event::KeyDown { keycode: Some(Keycode::Num1), .. } => scene.toggle_layer("axis")?,
+                Event::KeyDown { keycode: Some(Keycode::Num2), .. } => scene.toggle_layer("mohr")?,
+                Event::KeyDown { keycode: Some(Keycode::Num3), .. } => scene.toggle_layer("graph")?,
+                Event::KeyDown { keycode: Some(Keycode::Num4), .. } => scene.toggle_layer("complexity")?,
+                Event::KeyDown { keycode: Some(Keycode::Num5), .. } => scene.toggle_layer("polar")?,
+                Event::KeyDown { keycode: Some(Keycode::R), .. } => toggle_recording = true,
+                Event::KeyDown { keycode: Some(Keycode::T), .. } => theme = (theme + 1) % themes.len(),
+                // The cursor on the axis follows the mouse
+                Event::MouseMotion { .. } if scene.is_layer_visible("axis") => scene.mark_dirty(),
+                Event::Window { .. } => scene.mark_dirty(),
                 _ => {}
             }
         }
 
-        renderer.clear();
-        // renderer.draw_object(&axis)?;
         // renderer.draw_arrow(PixelCoordinate2D::new(0, 0), PixelCoordinate2D::new(100, 100), RED)?;
-        // renderer.draw_object(&mohr)?;
-        renderer.draw_object(&graph)?;
-
-        renderer.present();
+        if *renderer.theme() != themes[theme] {
+            renderer.set_theme(themes[theme]);
+            scene.mark_dirty();
+        }
+        if toggle_recording {
+            if renderer.is_recording() {
+                let frames = renderer.stop_recording()?;
+                log::info!("Saved {} frames to recording.gif", frames);
+            } else {
+                renderer.start_recording(Recorder::gif("recording.gif", config::FRAMERATE));
+            }
+        }
+        // A recording needs every frame to keep its timing, even when nothing changed
+        if renderer.is_recording() {
+            scene.mark_dirty();
+        }
+        scene.render(&mut renderer)?;
 
         std::thread::sleep(Duration::new(0, 1000000000 / config::FRAMERATE));
     }
 
+    if renderer.is_recording() {
+        renderer.stop_recording()?;
+    }
+
     Ok(())
 }
 
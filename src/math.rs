@@ -0,0 +1,320 @@
+//! A small LaTeX-like markup for labels: `\sigma` style symbols, `_` and `^` scripts, `{}` groups and `\frac{}{}`.
+//! It is laid out into plain text runs at different sizes, so it is drawn through the same font pipeline as any other text.
+
+use std::iter::Peekable;
+use std::str::Chars;
+use crate::font::FontManager;
+use crate::error::GrapherError;
+
+// Sizes and offsets as fractions of the size of the text they are attached to
+const SCRIPT_SCALE: f32 = 0.7;
+const FRACTION_SCALE: f32 = 0.8;
+const SUPERSCRIPT_RAISE: f32 = 0.4;
+const SUBSCRIPT_DROP: f32 = 0.25;
+// Height of the fraction bar above the baseline, about the middle of a lowercase letter
+const FRACTION_AXIS: f32 = 0.3;
+const MIN_SIZE: u16 = 6;
+
+const SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ε"), ("zeta", "ζ"),
+    ("eta", "η"), ("theta", "θ"), ("iota", "ι"), ("kappa", "κ"), ("lambda", "λ"), ("mu", "μ"),
+    ("nu", "ν"), ("xi", "ξ"), ("pi", "π"), ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"),
+    ("upsilon", "υ"), ("phi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"),
+    ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"), ("Pi", "Π"),
+    ("Sigma", "Σ"), ("Phi", "Φ"), ("Psi", "Ψ"), ("Omega", "Ω"),
+    ("infty", "∞"), ("pm", "±"), ("mp", "∓"), ("times", "×"), ("cdot", "·"), ("div", "÷"),
+    ("le", "≤"), ("leq", "≤"), ("ge", "≥"), ("geq", "≥"), ("ne", "≠"), ("neq", "≠"), ("approx", "≈"),
+    ("partial", "∂"), ("nabla", "∇"), ("circ", "∘"), ("degree", "°"), ("sqrt", "√"), ("sum", "∑"),
+    ("int", "∫"), ("to", "→"), ("rightarrow", "→"), ("leftarrow", "←"), ("prime", "′")
+];
+
+enum Node {
+    Text(String),
+    Group(Vec<Node>),
+    Scripts { base: Box<Node>, sub: Option<Box<Node>>, sup: Option<Box<Node>> },
+    Fraction(Box<Node>, Box<Node>)
+}
+
+fn parse_list(chars: &mut Peekable<Chars>, nested: bool) -> Result<Vec<Node>, GrapherError> {
+    let mut nodes = Vec::new();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '}' => {
+                chars.next();
+                return if nested { Ok(nodes) } else { Err(GrapherError::Parse("Unmatched '}' in math markup".to_string())) };
+            }
+            '_' | '^' => {
+                chars.next();
+                let script = parse_atom(chars)?;
+                // Scripts only attach to the last character, not the whole word before them
+                let base = match nodes.pop() {
+                    Some(Node::Text(mut text)) if text.chars().count() > 1 => {
+                        let last = text.pop().unwrap();
+                        nodes.push(Node::Text(text));
+                        Node::Text(last.to_string())
+                    }
+                    Some(node) => node,
+                    None => Node::Text(String::new())
+                };
+                nodes.push(attach_script(base, c == '^', script)?);
+            }
+            _ => {
+                let atom = parse_atom(chars)?;
+                match (nodes.last_mut(), atom) {
+                    (Some(Node::Text(text)), Node::Text(next)) => text.push_str(&next),
+                    (_, atom) => nodes.push(atom)
+                }
+            }
+        }
+    }
+    if nested {
+        Err(GrapherError::Parse("Missing '}' in math markup".to_string()))
+    } else {
+        Ok(nodes)
+    }
+}
+
+fn attach_script(base: Node, superscript: bool, script: Node) -> Result<Node, GrapherError> {
+    let (base, mut sub, mut sup) = match base {
+        Node::Scripts { base, sub, sup } => (base, sub, sup),
+        base => (Box::new(base), None, None)
+    };
+    let slot = if superscript { &mut sup } else { &mut sub };
+    if slot.is_some() {
+        return Err(GrapherError::Parse(format!("Double {} in math markup", if superscript { "superscript" } else { "subscript" })));
+    }
+    *slot = Some(Box::new(script));
+    Ok(Node::Scripts { base, sub, sup })
+}
+
+/// A single character, a command or a `{}` group.
+fn parse_atom(chars: &mut Peekable<Chars>) -> Result<Node, GrapherError> {
+    match chars.next() {
+        None => Err(GrapherError::Parse("Math markup ends where something was expected".to_string())),
+        Some('{') => Ok(Node::Group(parse_list(chars, true)?)),
+        Some('\\') => parse_command(chars),
+        Some(c) => Ok(Node::Text(c.to_string()))
+    }
+}
+
+fn parse_command(chars: &mut Peekable<Chars>) -> Result<Node, GrapherError> {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+        name.push(c);
+        chars.next();
+    }
+    if name.is_empty() {
+        // Escaped characters like \_ and \{, with \, and "\ " for spaces
+        return match chars.next() {
+            Some(',') | Some(' ') => Ok(Node::Text(" ".to_string())),
+            Some(c) => Ok(Node::Text(c.to_string())),
+            None => Err(GrapherError::Parse("Math markup ends with a '\\'".to_string()))
+        };
+    }
+    // Like TeX, a space after a command name only ends the name
+    if chars.peek() == Some(&' ') {
+        chars.next();
+    }
+    if name == "frac" {
+        let numerator = parse_atom(chars)?;
+        let denominator = parse_atom(chars)?;
+        return Ok(Node::Fraction(Box::new(numerator), Box::new(denominator)));
+    }
+    SYMBOLS.iter()
+        .find(|(symbol, _)| *symbol == name)
+        .map(|(_, text)| Node::Text(text.to_string()))
+        .ok_or(GrapherError::Parse(format!("Unknown math command \\{}", name)))
+}
+
+/// A run of text in one font and size, positioned by the start of its baseline.
+pub struct MathRun {
+    pub text: String,
+    pub size: u16,
+    pub position: (f32, f32)
+}
+
+/// A horizontal line, used for fraction bars.
+pub struct MathRule {
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    pub width: f32
+}
+
+/// Laid out markup with its baseline at y = 0, ascent is measured up from it and descent down.
+pub struct MathBox {
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub runs: Vec<MathRun>,
+    pub rules: Vec<MathRule>
+}
+
+impl MathBox {
+    fn empty() -> MathBox {
+        MathBox { width: 0.0, ascent: 0.0, descent: 0.0, runs: Vec::new(), rules: Vec::new() }
+    }
+
+    /// Places another box with its baseline origin at `(x, y)` inside this one, growing it to fit.
+    fn add(&mut self, other: MathBox, x: f32, y: f32) {
+        self.width = self.width.max(x + other.width);
+        self.ascent = self.ascent.max(other.ascent - y);
+        self.descent = self.descent.max(other.descent + y);
+        self.runs.extend(other.runs.into_iter().map(|run| MathRun { position: (run.position.0 + x, run.position.1 + y), ..run }));
+        self.rules.extend(other.rules.into_iter().map(|rule| MathRule {
+            start: (rule.start.0 + x, rule.start.1 + y),
+            end: (rule.end.0 + x, rule.end.1 + y),
+            ..rule
+        }));
+    }
+}
+
+fn scaled(size: u16, scale: f32) -> u16 {
+    ((size as f32 * scale).round() as u16).max(MIN_SIZE)
+}
+
+fn layout_text(fonts: &mut FontManager, text: &str, size: u16) -> Result<MathBox, GrapherError> {
+    let mut output = MathBox {
+        ascent: fonts.ascent(size)? as f32,
+        descent: fonts.descent(size)? as f32,
+        ..MathBox::empty()
+    };
+    // Split wherever the font changes so Greek letters from the fallback font sit next to the rest
+    let mut pieces: Vec<(String, bool)> = Vec::new();
+    for c in text.chars() {
+        let covered = fonts.covers(&c.to_string())?;
+        match pieces.last_mut() {
+            Some((piece, last_covered)) if *last_covered == covered => piece.push(c),
+            _ => pieces.push((c.to_string(), covered))
+        }
+    }
+    for (piece, _) in pieces {
+        let width = fonts.size_of(&piece, size)?.0 as f32;
+        output.runs.push(MathRun { text: piece, size, position: (output.width, 0.0) });
+        output.width += width;
+    }
+    Ok(output)
+}
+
+fn layout_node(fonts: &mut FontManager, node: &Node, size: u16) -> Result<MathBox, GrapherError> {
+    match node {
+        Node::Text(text) => layout_text(fonts, text, size),
+        Node::Group(nodes) => layout_list(fonts, nodes, size),
+        Node::Scripts { base, sub, sup } => {
+            let mut output = layout_node(fonts, base, size)?;
+            let x = output.width;
+            let script_size = scaled(size, SCRIPT_SCALE);
+            if let Some(sup) = sup {
+                let sup = layout_node(fonts, sup, script_size)?;
+                output.add(sup, x, -SUPERSCRIPT_RAISE * size as f32);
+            }
+            if let Some(sub) = sub {
+                let sub = layout_node(fonts, sub, script_size)?;
+                output.add(sub, x, SUBSCRIPT_DROP * size as f32);
+            }
+            Ok(output)
+        }
+        Node::Fraction(numerator, denominator) => {
+            let part_size = scaled(size, FRACTION_SCALE);
+            let numerator = layout_node(fonts, numerator, part_size)?;
+            let denominator = layout_node(fonts, denominator, part_size)?;
+            let thickness = (size as f32 / 16.0).max(1.0);
+            let gap = thickness * 2.0;
+            let padding = size as f32 * 0.1;
+            let axis = -FRACTION_AXIS * size as f32;
+            let width = numerator.width.max(denominator.width) + padding * 2.0;
+            let mut output = MathBox::empty();
+            output.rules.push(MathRule { start: (0.0, axis), end: (width, axis), width: thickness });
+            output.width = width;
+            let numerator_x = (width - numerator.width) / 2.0;
+            let numerator_y = axis - gap - numerator.descent;
+            output.add(numerator, numerator_x, numerator_y);
+            let denominator_x = (width - denominator.width) / 2.0;
+            let denominator_y = axis + gap + denominator.ascent;
+            output.add(denominator, denominator_x, denominator_y);
+            Ok(output)
+        }
+    }
+}
+
+fn layout_list(fonts: &mut FontManager, nodes: &[Node], size: u16) -> Result<MathBox, GrapherError> {
+    let mut output = MathBox {
+        ascent: fonts.ascent(size)? as f32,
+        descent: fonts.descent(size)? as f32,
+        ..MathBox::empty()
+    };
+    for node in nodes {
+        let part = layout_node(fonts, node, size)?;
+        let x = output.width;
+        output.add(part, x, 0.0);
+    }
+    Ok(output)
+}
+
+/// Parses a line of markup and lays it out at the given size.
+pub fn layout(fonts: &mut FontManager, markup: &str, size: u16) -> Result<MathBox, GrapherError> {
+    let nodes = parse_list(&mut markup.chars().peekable(), false)?;
+    layout_list(fonts, &nodes, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parsed tree written out with explicit braces, so the structure can be compared as text.
+    fn describe(nodes: &[Node]) -> String {
+        nodes.iter().map(|node| match node {
+            Node::Text(text) => text.clone(),
+            Node::Group(nodes) => format!("{{{}}}", describe(nodes)),
+            Node::Scripts { base, sub, sup } => {
+                let mut text = describe(std::slice::from_ref(base));
+                if let Some(sub) = sub {
+                    text += &format!("_[{}]", describe(std::slice::from_ref(sub)));
+                }
+                if let Some(sup) = sup {
+                    text += &format!("^[{}]", describe(std::slice::from_ref(sup)));
+                }
+                text
+            }
+            Node::Fraction(numerator, denominator) => format!(
+                "frac[{}][{}]",
+                describe(std::slice::from_ref(numerator)),
+                describe(std::slice::from_ref(denominator))
+            )
+        }).collect::<Vec<_>>().join("|")
+    }
+
+    fn parse(markup: &str) -> Result<String, GrapherError> {
+        parse_list(&mut markup.chars().peekable(), false).map(|nodes| describe(&nodes))
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(parse("\\sigma").unwrap(), "σ");
+        assert_eq!(parse("2\\pi r").unwrap(), "2πr");
+        assert_eq!(parse("\\Delta x").unwrap(), "Δx");
+        assert_eq!(parse("a\\,b\\_c").unwrap(), "a b_c");
+    }
+
+    #[test]
+    fn scripts_attach_to_the_last_character() {
+        assert_eq!(parse("x^2").unwrap(), "x^[2]");
+        assert_eq!(parse("ax^2").unwrap(), "a|x^[2]");
+        assert_eq!(parse("\\tau_{max}").unwrap(), "τ_[{max}]");
+        assert_eq!(parse("x_i^2").unwrap(), "x_[i]^[2]");
+        assert_eq!(parse("e^{i\\pi}").unwrap(), "e^[{iπ}]");
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(parse("\\frac{a}{b}").unwrap(), "frac[{a}][{b}]");
+        assert_eq!(parse("\\frac{\\sigma_1}{2}").unwrap(), "frac[{σ_[1]}][{2}]");
+        assert_eq!(parse("\\frac12").unwrap(), "frac[1][2]");
+    }
+
+    #[test]
+    fn malformed_markup_is_a_parse_error() {
+        for markup in ["{x", "x}", "\\frac{a}", "x^", "x^2^3", "x_1_2", "\\nosuchcommand", "trailing\\"] {
+            assert!(matches!(parse(markup), Err(GrapherError::Parse(_))), "{:?} was accepted", markup);
+        }
+    }
+}
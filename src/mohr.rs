@@ -1,8 +1,9 @@
+use sdl2::event::Event;
 use crate::axis::{Axis2D, DrawableAxis2D};
-use crate::colors::{BLUE, GREEN, LIGHT_GRAY, RED};
 use crate::config::{CENTER_X, CENTER_Y, WINDOW_WIDTH};
 use crate::coordinate::{CartesianCoordinate2D, PixelCoordinate2D};
 use crate::renderer::{Drawable, Renderer};
+use crate::error::GrapherError;
 
 pub struct MohrsCircle {
     pub center: CartesianCoordinate2D,
@@ -84,6 +85,9 @@ impl MohrsCircle {
             scale,
             0.01
         );
+        axis.set_labels("\\sigma", "\\tau");
+        axis.set_grid(true, true);
+        axis.set_interactive(true);
         axis.add_object(Box::new(circle1));
         axis.add_object(Box::new(circle2));
         axis.add_object(Box::new(circle3));
@@ -100,26 +104,31 @@ impl MohrsCircle {
 }
 
 impl Drawable for MohrsCircle {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
         renderer.draw_object(&self.axis)
     }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        self.axis.handle_event(event)
+    }
 }
 
 impl DrawableAxis2D for Circle {
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
         renderer.draw_circle(
             self.point1.to_pixel(&axis2d),
             self.radius as u32,
-            GREEN
+            theme.accent(0)
         )?;
         renderer.draw_dashed_line(
             self.point1.to_pixel(&axis2d),
             self.point2.to_pixel(&axis2d),
-            LIGHT_GRAY,
+            theme.grid,
             5,
             5
         )?;
-        renderer.draw_dot(self.center.to_pixel(&axis2d), BLUE)
+        renderer.draw_dot(self.center.to_pixel(&axis2d), theme.accent(1))
     }
 
     // TODO: Implement snapping to nearest circle
@@ -0,0 +1,224 @@
+//! Paths made of straight and Bezier segments, for curves that should stay smooth at any size.
+
+type Point = (f32, f32);
+
+// How far a flattened curve may stray from the real one, in pixels
+const FLATTEN_TOLERANCE: f32 = 0.25;
+const MAX_CURVE_SEGMENTS: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PathSegment {
+    MoveTo(Point),
+    LineTo(Point),
+    /// Control point then end point
+    QuadTo(Point, Point),
+    /// Both control points then end point
+    CubicTo(Point, Point, Point),
+    /// Joins back to the start of the current subpath
+    Close
+}
+
+/// A sequence of subpaths in sub-pixel positions, each starting with a `MoveTo`.
+/// Built up the same way as SVG and PDF paths so the vector backends can keep the curves exact.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Path {
+    pub segments: Vec<PathSegment>
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+fn second_difference(a: Point, b: Point, c: Point) -> f32 {
+    ((a.0 - 2.0 * b.0 + c.0).powi(2) + (a.1 - 2.0 * b.1 + c.1).powi(2)).sqrt()
+}
+
+/// Wang's formula, the number of straight pieces that keeps a Bezier curve within the tolerance
+fn curve_segments(degree: f32, curvature: f32) -> usize {
+    let segments = (degree * (degree - 1.0) / 8.0 * curvature / FLATTEN_TOLERANCE).sqrt().ceil();
+    if segments.is_finite() {
+        (segments as usize).clamp(1, MAX_CURVE_SEGMENTS)
+    } else {
+        1
+    }
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path { segments: Vec::new() }
+    }
+
+    pub fn move_to(mut self, point: Point) -> Path {
+        self.segments.push(PathSegment::MoveTo(point));
+        self
+    }
+
+    pub fn line_to(mut self, point: Point) -> Path {
+        self.segments.push(PathSegment::LineTo(point));
+        self
+    }
+
+    pub fn quad_to(mut self, control: Point, end: Point) -> Path {
+        self.segments.push(PathSegment::QuadTo(control, end));
+        self
+    }
+
+    pub fn cubic_to(mut self, control1: Point, control2: Point, end: Point) -> Path {
+        self.segments.push(PathSegment::CubicTo(control1, control2, end));
+        self
+    }
+
+    pub fn close(mut self) -> Path {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    /// A smooth curve passing through every point in order, as a Catmull-Rom spline converted to cubic segments.
+    pub fn spline(points: &[Point]) -> Path {
+        let mut path = Path::new();
+        if points.is_empty() {
+            return path;
+        }
+        path = path.move_to(points[0]);
+        for i in 0..points.len() - 1 {
+            // The ends are repeated so the curve still reaches the first and last points
+            let previous = points[i.saturating_sub(1)];
+            let start = points[i];
+            let end = points[i + 1];
+            let next = points[(i + 2).min(points.len() - 1)];
+            path = path.cubic_to(
+                (start.0 + (end.0 - previous.0) / 6.0, start.1 + (end.1 - previous.1) / 6.0),
+                (end.0 - (next.0 - start.0) / 6.0, end.1 - (next.1 - start.1) / 6.0),
+                end
+            );
+        }
+        path
+    }
+
+    /// Every subpath as straight line segments, along with whether it was closed.
+    pub fn flatten(&self) -> Vec<(Vec<Point>, bool)> {
+        let mut subpaths = Vec::new();
+        let mut current: Vec<Point> = Vec::new();
+        for &segment in &self.segments {
+            match segment {
+                PathSegment::MoveTo(point) => {
+                    if current.len() > 1 {
+                        subpaths.push((std::mem::take(&mut current), false));
+                    }
+                    current = vec![point];
+                }
+                PathSegment::LineTo(point) => current.push(point),
+                PathSegment::QuadTo(control, end) => {
+                    let start = current.last().copied().unwrap_or(control);
+                    let segments = curve_segments(2.0, second_difference(start, control, end));
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        current.push(lerp(lerp(start, control, t), lerp(control, end, t), t));
+                    }
+                }
+                PathSegment::CubicTo(control1, control2, end) => {
+                    let start = current.last().copied().unwrap_or(control1);
+                    let curvature = second_difference(start, control1, control2).max(second_difference(control1, control2, end));
+                    let segments = curve_segments(3.0, curvature);
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        let a = lerp(lerp(start, control1, t), lerp(control1, control2, t), t);
+                        let b = lerp(lerp(control1, control2, t), lerp(control2, end, t), t);
+                        current.push(lerp(a, b, t));
+                    }
+                }
+                PathSegment::Close => {
+                    if let Some(&start) = current.first() {
+                        if current.len() > 1 {
+                            subpaths.push((std::mem::take(&mut current), true));
+                        }
+                        // Anything drawn after a close carries on from the start of the closed subpath
+                        current = vec![start];
+                    }
+                }
+            }
+        }
+        if current.len() > 1 {
+            subpaths.push((current, false));
+        }
+        subpaths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance_to_segment(point: Point, a: Point, b: Point) -> f32 {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let length = dx * dx + dy * dy;
+        let t = if length == 0.0 { 0.0 } else { (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0) };
+        ((point.0 - a.0 - t * dx).powi(2) + (point.1 - a.1 - t * dy).powi(2)).sqrt()
+    }
+
+    fn cubic(start: Point, control1: Point, control2: Point, end: Point, t: f32) -> Point {
+        let a = lerp(lerp(start, control1, t), lerp(control1, control2, t), t);
+        let b = lerp(lerp(control1, control2, t), lerp(control2, end, t), t);
+        lerp(a, b, t)
+    }
+
+    #[test]
+    fn lines_flatten_to_their_points() {
+        let path = Path::new().move_to((0.0, 0.0)).line_to((10.0, 0.0)).line_to((10.0, 5.0));
+        assert_eq!(path.flatten(), vec![(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)], false)]);
+    }
+
+    #[test]
+    fn curves_end_on_their_end_points() {
+        let quad = Path::new().move_to((0.0, 0.0)).quad_to((50.0, 100.0), (100.0, 0.0)).flatten();
+        assert_eq!(quad[0].0.first(), Some(&(0.0, 0.0)));
+        assert_eq!(quad[0].0.last(), Some(&(100.0, 0.0)));
+        let cubic = Path::new().move_to((0.0, 0.0)).cubic_to((0.0, 80.0), (120.0, 80.0), (120.0, 0.0)).flatten();
+        assert_eq!(cubic[0].0.last(), Some(&(120.0, 0.0)));
+    }
+
+    #[test]
+    fn cubic_stays_within_the_tolerance() {
+        let (start, control1, control2, end) = ((0.0, 0.0), (0.0, 300.0), (400.0, -300.0), (400.0, 0.0));
+        let points = &Path::new().move_to(start).cubic_to(control1, control2, end).flatten()[0].0;
+        assert!(points.len() > 2);
+        // Sample the real curve densely and check every point is close to the flattened line
+        for i in 0..=1000 {
+            let point = cubic(start, control1, control2, end, i as f32 / 1000.0);
+            let nearest = points.windows(2).map(|pair| distance_to_segment(point, pair[0], pair[1])).fold(f32::INFINITY, f32::min);
+            assert!(nearest <= FLATTEN_TOLERANCE + 1e-3, "{:?} is {} away", point, nearest);
+        }
+    }
+
+    #[test]
+    fn straight_curves_use_one_segment() {
+        let flat = Path::new().move_to((0.0, 0.0)).cubic_to((1.0, 1.0), (2.0, 2.0), (3.0, 3.0)).flatten();
+        assert_eq!(flat[0].0, vec![(0.0, 0.0), (3.0, 3.0)]);
+    }
+
+    #[test]
+    fn subpaths_split_on_move_and_close() {
+        let path = Path::new()
+            .move_to((0.0, 0.0)).line_to((1.0, 0.0)).line_to((1.0, 1.0)).close()
+            .move_to((5.0, 5.0)).line_to((6.0, 5.0));
+        let subpaths = path.flatten();
+        assert_eq!(subpaths.len(), 2);
+        assert!(subpaths[0].1);
+        assert!(!subpaths[1].1);
+        assert_eq!(subpaths[1].0, vec![(5.0, 5.0), (6.0, 5.0)]);
+    }
+
+    #[test]
+    fn spline_passes_through_every_point() {
+        let points = [(0.0, 0.0), (10.0, 20.0), (30.0, 5.0), (40.0, 40.0)];
+        let path = Path::spline(&points);
+        assert_eq!(path.segments[0], PathSegment::MoveTo(points[0]));
+        let ends: Vec<Point> = path.segments[1..].iter().map(|segment| match *segment {
+            PathSegment::CubicTo(_, _, end) => end,
+            other => panic!("expected a cubic, got {:?}", other)
+        }).collect();
+        assert_eq!(ends, points[1..]);
+        assert!(Path::spline(&[]).segments.is_empty());
+        assert!(Path::spline(&[(1.0, 1.0)]).flatten().is_empty());
+    }
+}
@@ -0,0 +1,655 @@
+use std::any::Any;
+use std::collections::BTreeSet;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use crate::backend::{push_line, Backend};
+use crate::coordinate::PixelCoordinate2D;
+use crate::font::{FontManager, FALLBACK_FONT_DATA, FONT_DATA};
+use crate::path::{Path, PathSegment};
+use crate::stroke::{LineCap, LineJoin, StrokeStyle};
+use crate::error::GrapherError;
+
+const FONT_NAME: &str = "JetBrainsMono-Regular";
+const FALLBACK_FONT_NAME: &str = "DejaVuSansMono";
+// Control point distance for approximating a quarter circle with a cubic bezier
+const KAPPA: f32 = 0.552_284_8;
+
+/// The handful of TrueType tables needed to embed the font and lay text out in a PDF.
+struct FontMetrics {
+    units_per_em: f32,
+    bbox: [i16; 4],
+    ascent: i16,
+    descent: i16,
+    cmap: usize,
+    hmtx: usize,
+    h_metric_count: u16
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_i16(data: &[u8], offset: usize) -> i16 {
+    read_u16(data, offset) as i16
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+impl FontMetrics {
+    fn parse(data: &[u8]) -> Result<FontMetrics, GrapherError> {
+        let table = |tag: &[u8; 4]| -> Result<usize, GrapherError> {
+            (0..read_u16(data, 4) as usize)
+                .map(|i| 12 + 16 * i)
+                .find(|&record| &data[record..record + 4] == tag)
+                .map(|record| read_u32(data, record + 8) as usize)
+                .ok_or(GrapherError::Font(format!("Font is missing the {} table", String::from_utf8_lossy(tag))))
+        };
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let cmap = table(b"cmap")?;
+        // Only the windows unicode BMP subtable (format 4) is supported
+        let cmap = (0..read_u16(data, cmap + 2) as usize)
+            .map(|i| cmap + 4 + 8 * i)
+            .find(|&record| read_u16(data, record) == 3 && read_u16(data, record + 2) == 1)
+            .map(|record| cmap + read_u32(data, record + 4) as usize)
+            .filter(|&subtable| read_u16(data, subtable) == 4)
+            .ok_or(GrapherError::Font("Font has no unicode cmap".to_string()))?;
+        Ok(FontMetrics {
+            units_per_em: read_u16(data, head + 18) as f32,
+            bbox: [
+                read_i16(data, head + 36),
+                read_i16(data, head + 38),
+                read_i16(data, head + 40),
+                read_i16(data, head + 42)
+            ],
+            ascent: read_i16(data, hhea + 4),
+            descent: read_i16(data, hhea + 6),
+            cmap,
+            hmtx: table(b"hmtx")?,
+            h_metric_count: read_u16(data, hhea + 34)
+        })
+    }
+
+    fn glyph_id(&self, data: &[u8], character: char) -> u16 {
+        let code = character as u32;
+        if code > 0xFFFF {
+            return 0;
+        }
+        let code = code as u16;
+        let seg_count = read_u16(data, self.cmap + 6) as usize / 2;
+        let end_codes = self.cmap + 14;
+        let start_codes = end_codes + seg_count * 2 + 2;
+        let id_deltas = start_codes + seg_count * 2;
+        let id_range_offsets = id_deltas + seg_count * 2;
+        for segment in 0..seg_count {
+            if read_u16(data, end_codes + segment * 2) < code {
+                continue;
+            }
+            let start = read_u16(data, start_codes + segment * 2);
+            if start > code {
+                return 0;
+            }
+            let delta = read_u16(data, id_deltas + segment * 2);
+            let range_offset = read_u16(data, id_range_offsets + segment * 2) as usize;
+            if range_offset == 0 {
+                return code.wrapping_add(delta);
+            }
+            let glyph_offset = id_range_offsets + segment * 2 + range_offset + (code - start) as usize * 2;
+            let glyph = read_u16(data, glyph_offset);
+            return if glyph == 0 { 0 } else { glyph.wrapping_add(delta) };
+        }
+        0
+    }
+
+    fn advance(&self, data: &[u8], glyph: u16) -> u16 {
+        let index = glyph.min(self.h_metric_count - 1) as usize;
+        read_u16(data, self.hmtx + index * 4)
+    }
+
+    /// Scales font units to the 1/1000 text space units PDF expects.
+    fn to_pdf_units(&self, value: i32) -> i32 {
+        (value as f32 * 1000.0 / self.units_per_em) as i32
+    }
+}
+
+/// Records everything drawn as PDF page content, with JetBrainsMono embedded for text and DejaVuSansMono for anything it is missing.
+/// Each `present` finishes a page, so a sequence of frames becomes a multi-page document.
+pub struct PdfBackend {
+    width: u32,
+    height: u32,
+    fonts: [EmbeddedFont; 2],
+    pages: Vec<String>,
+    content: String,
+    alphas: BTreeSet<(&'static str, u8)>,
+    translucent_page: bool,
+    /// The origin and clip rectangle in effect, drawn inside its own `q`/`Q` so it can be undone
+    view: Option<((i32, i32), Option<Rect>)>,
+    /// How much of the content was written by `present` setting the view back up, not by drawing
+    page_start: usize
+}
+
+/// A font and the characters drawn with it, everything needed to write out its objects.
+struct EmbeddedFont {
+    name: &'static str,
+    resource: &'static str,
+    data: &'static [u8],
+    metrics: FontMetrics,
+    used_characters: BTreeSet<char>
+}
+
+impl EmbeddedFont {
+    fn new(name: &'static str, resource: &'static str, data: &'static [u8]) -> Result<EmbeddedFont, GrapherError> {
+        Ok(EmbeddedFont { name, resource, data, metrics: FontMetrics::parse(data)?, used_characters: BTreeSet::new() })
+    }
+
+    /// Control characters draw nothing, so like `FontManager::covers` they do not count against a font.
+    fn covers(&self, text: &str) -> bool {
+        text.chars().all(|c| c.is_control() || self.metrics.glyph_id(self.data, c) != 0)
+    }
+
+    /// The six objects describing the font, numbered from `id`.
+    fn objects(&self, id: usize) -> Vec<Vec<u8>> {
+        let metrics = &self.metrics;
+        let mut objects = Vec::new();
+        objects.push(format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+            self.name, id + 1, id + 5
+        ).into_bytes());
+
+        // Character ids are unicode code points, mapped to glyphs through CIDToGIDMap
+        let mut widths = String::new();
+        let mut cid_to_gid = vec![0u8; 2 * (self.used_characters.iter().last().map_or(0, |&c| c as usize) + 1)];
+        for &character in &self.used_characters {
+            let glyph = metrics.glyph_id(self.data, character);
+            let cid = character as usize;
+            cid_to_gid[cid * 2..cid * 2 + 2].copy_from_slice(&glyph.to_be_bytes());
+            let advance = metrics.to_pdf_units(metrics.advance(self.data, glyph) as i32);
+            widths.push_str(&format!("{} [{}] ", cid, advance));
+        }
+        objects.push(format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+            /FontDescriptor {} 0 R /W [{}] /CIDToGIDMap {} 0 R >>",
+            self.name, id + 2, widths, id + 4
+        ).into_bytes());
+        let bbox = metrics.bbox.map(|v| metrics.to_pdf_units(v as i32));
+        objects.push(format!(
+            "<< /Type /FontDescriptor /FontName /{} /Flags 33 /FontBBox [{} {} {} {}] /ItalicAngle 0 \
+            /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 {} 0 R >>",
+            self.name, bbox[0], bbox[1], bbox[2], bbox[3],
+            metrics.to_pdf_units(metrics.ascent as i32),
+            metrics.to_pdf_units(metrics.descent as i32),
+            metrics.to_pdf_units(metrics.ascent as i32),
+            id + 3
+        ).into_bytes());
+        objects.push(stream(&format!("/Length1 {}", self.data.len()), self.data));
+        objects.push(stream("", &cid_to_gid));
+        objects.push(stream("", to_unicode_cmap().as_bytes()));
+        objects
+    }
+}
+
+fn number(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl PdfBackend {
+    pub fn new(width: u32, height: u32) -> Result<PdfBackend, GrapherError> {
+        Ok(PdfBackend {
+            width,
+            height,
+            fonts: [
+                EmbeddedFont::new(FONT_NAME, "F1", FONT_DATA)?,
+                EmbeddedFont::new(FALLBACK_FONT_NAME, "F2", FALLBACK_FONT_DATA)?
+            ],
+            pages: Vec::new(),
+            content: String::new(),
+            alphas: BTreeSet::new(),
+            translucent_page: false,
+            view: None,
+            page_start: 0
+        })
+    }
+
+    /// PDF measures y up from the bottom of the page, one pixel is drawn as one point.
+    fn flip(&self, y: f32) -> f32 {
+        self.height as f32 - y
+    }
+
+    fn set_stroke_color(&mut self, color: Color) {
+        push_line!(
+            self.content, "{} {} {} RG",
+            number(color.r as f32 / 255.0), number(color.g as f32 / 255.0), number(color.b as f32 / 255.0)
+        );
+        self.set_alpha("SA", color.a);
+    }
+
+    fn set_fill_color(&mut self, color: Color) {
+        push_line!(
+            self.content, "{} {} {} rg",
+            number(color.r as f32 / 255.0), number(color.g as f32 / 255.0), number(color.b as f32 / 255.0)
+        );
+        self.set_alpha("FA", color.a);
+    }
+
+    fn open_view(&mut self) {
+        let Some((origin, clip)) = self.view else {
+            return;
+        };
+        self.content.push_str("q\n");
+        if let Some(clip) = clip {
+            push_line!(
+                self.content, "{} {} {} {} re W n",
+                clip.x(), number(self.flip((clip.y() + clip.height() as i32) as f32)), clip.width(), clip.height()
+            );
+        }
+        if origin != (0, 0) {
+            push_line!(self.content, "1 0 0 1 {} {} cm", origin.0, -origin.1);
+        }
+    }
+
+    /// Opacity lives in graphics state dictionaries, `FA` ones set it for fills and `SA` ones for strokes.
+    fn set_alpha(&mut self, prefix: &'static str, alpha: u8) {
+        // Once something on the page was translucent the opacity is always set,
+        // since a `Q` can bring back whatever was in use before its `q`
+        if alpha == 255 && !self.translucent_page {
+            return;
+        }
+        self.translucent_page = true;
+        self.alphas.insert((prefix, alpha));
+        push_line!(self.content, "/{}{} gs", prefix, alpha);
+    }
+
+    /// Sets up the line state for a stroke, callers wrap it in `q`/`Q` so it does not leak into later drawing.
+    fn set_stroke_style(&mut self, style: &StrokeStyle) {
+        self.set_stroke_color(style.color);
+        let cap = match style.cap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2
+        };
+        let join = match style.join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2
+        };
+        push_line!(self.content, "{} w {} J {} j", number(style.width), cap, join);
+        if let Some(dash) = style.dash.as_ref().filter(|_| style.is_dashed()) {
+            let lengths: Vec<String> = dash.normalized_lengths().iter().map(|&length| number(length)).collect();
+            push_line!(self.content, "[{}] {} d", lengths.join(" "), number(dash.phase));
+        }
+    }
+
+    fn circle_path(&mut self, center: (f32, f32), radius: f32) {
+        let x = center.0 + 0.5;
+        let y = self.flip(center.1 + 0.5);
+        let r = radius;
+        let k = r * KAPPA;
+        push_line!(
+            self.content,
+            "{} {} m {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c",
+            number(x + r), number(y),
+            number(x + r), number(y + k), number(x + k), number(y + r), number(x), number(y + r),
+            number(x - k), number(y + r), number(x - r), number(y + k), number(x - r), number(y),
+            number(x - r), number(y - k), number(x - k), number(y - r), number(x), number(y - r),
+            number(x + k), number(y - r), number(x + r), number(y - k), number(x + r), number(y)
+        );
+    }
+
+    fn path(&mut self, path: &Path) {
+        let mut start = (0.0, 0.0);
+        let mut current = (0.0, 0.0);
+        for &segment in &path.segments {
+            match segment {
+                PathSegment::MoveTo(to) => {
+                    push_line!(self.content, "{} {} m", number(to.0 + 0.5), number(self.flip(to.1 + 0.5)));
+                    start = to;
+                    current = to;
+                }
+                PathSegment::LineTo(to) => {
+                    push_line!(self.content, "{} {} l", number(to.0 + 0.5), number(self.flip(to.1 + 0.5)));
+                    current = to;
+                }
+                PathSegment::QuadTo(control, to) => {
+                    // PDF only has cubic curves, a quadratic one is the cubic with its controls two thirds of the way to the control point
+                    let control1 = (current.0 + (control.0 - current.0) * 2.0 / 3.0, current.1 + (control.1 - current.1) * 2.0 / 3.0);
+                    let control2 = (to.0 + (control.0 - to.0) * 2.0 / 3.0, to.1 + (control.1 - to.1) * 2.0 / 3.0);
+                    self.cubic(control1, control2, to);
+                    current = to;
+                }
+                PathSegment::CubicTo(control1, control2, to) => {
+                    self.cubic(control1, control2, to);
+                    current = to;
+                }
+                PathSegment::Close => {
+                    self.content.push_str("h\n");
+                    current = start;
+                }
+            }
+        }
+    }
+
+    fn cubic(&mut self, control1: (f32, f32), control2: (f32, f32), to: (f32, f32)) {
+        push_line!(
+            self.content, "{} {} {} {} {} {} c",
+            number(control1.0 + 0.5), number(self.flip(control1.1 + 0.5)),
+            number(control2.0 + 0.5), number(self.flip(control2.1 + 0.5)),
+            number(to.0 + 0.5), number(self.flip(to.1 + 0.5))
+        );
+    }
+
+    /// The finished document, including the page currently being drawn if anything is on it.
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let mut pages = self.pages.clone();
+        if self.content.len() > self.page_start || pages.is_empty() {
+            let mut content = self.content.clone();
+            if self.view.is_some() {
+                content.push_str("Q\n");
+            }
+            pages.push(content);
+        }
+
+        // The fallback font is only embedded when something was drawn with it
+        let fonts: Vec<&EmbeddedFont> = self.fonts.iter().enumerate()
+            .filter(|(i, font)| *i == 0 || !font.used_characters.is_empty())
+            .map(|(_, font)| font)
+            .collect();
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        let first_page = 3 + fonts.len() * 6;
+        let page_ids: Vec<usize> = (0..pages.len()).map(|i| first_page + i * 2).collect();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objects.push(format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ).into_bytes());
+        for font in &fonts {
+            let id = objects.len() + 1;
+            objects.extend(font.objects(id));
+        }
+        let resources: Vec<String> = fonts.iter().enumerate()
+            .map(|(i, font)| format!("/{} {} 0 R", font.resource, 3 + i * 6))
+            .collect();
+        let graphics_states: Vec<String> = self.alphas.iter()
+            .map(|&(prefix, alpha)| format!(
+                "/{}{} << /{} {} >>",
+                prefix, alpha, if prefix == "FA" { "ca" } else { "CA" }, number(alpha as f32 / 255.0)
+            ))
+            .collect();
+
+        for (i, page) in pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << {} >> /ExtGState << {} >> >> /Contents {} 0 R >>",
+                self.width, self.height, resources.join(" "), graphics_states.join(" "), page_ids[i] + 1
+            ).into_bytes());
+            objects.push(stream("", page.as_bytes()));
+        }
+
+        let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = pdf.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            push_line!(trailer, "{:010} 00000 n ", offset);
+        }
+        trailer.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1, xref
+        ));
+        pdf.extend_from_slice(trailer.as_bytes());
+        pdf
+    }
+}
+
+fn stream(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< /Length {} {} >>\nstream\n", data.len(), dictionary).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+/// Character ids are already unicode, so text can be copied back out of the document.
+fn to_unicode_cmap() -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+        /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+        /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+        1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n"
+    );
+    // A bfrange may only vary in its last byte and a section may hold at most 100 of them,
+    // so the identity mapping is split into 256 ranges over three sections
+    let highs: Vec<u32> = (0..256).collect();
+    for chunk in highs.chunks(100) {
+        push_line!(cmap, "{} beginbfrange", chunk.len());
+        for high in chunk {
+            push_line!(cmap, "<{:02X}00> <{:02X}FF> <{:02X}00>", high, high, high);
+        }
+        cmap.push_str("endbfrange\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend");
+    cmap
+}
+
+impl Backend for PdfBackend {
+    fn size(&self) -> Result<(u32, u32), GrapherError> {
+        Ok((self.width, self.height))
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.content.clear();
+        self.page_start = 0;
+        self.set_fill_color(color);
+        push_line!(self.content, "0 0 {} {} re f", self.width, self.height);
+        self.open_view();
+    }
+
+    fn present(&mut self) {
+        if self.view.is_some() {
+            self.content.push_str("Q\n");
+        }
+        let page = std::mem::take(&mut self.content);
+        self.pages.push(page);
+        self.translucent_page = false;
+        self.open_view();
+        self.page_start = self.content.len();
+    }
+
+    fn draw_point(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.fill_rect(position, 1, 1, color)
+    }
+
+    fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.set_stroke_color(color);
+        push_line!(
+            self.content, "1 w 2 J {} {} m {} {} l S",
+            number(position1.x as f32 + 0.5), number(self.flip(position1.y as f32 + 0.5)),
+            number(position2.x as f32 + 0.5), number(self.flip(position2.y as f32 + 0.5))
+        );
+        Ok(())
+    }
+
+    fn draw_line_precise(&mut self, start: (f32, f32), end: (f32, f32), color: Color) -> Result<(), GrapherError> {
+        self.set_stroke_color(color);
+        push_line!(
+            self.content, "1 w 2 J {} {} m {} {} l S",
+            number(start.0 + 0.5), number(self.flip(start.1 + 0.5)),
+            number(end.0 + 0.5), number(self.flip(end.1 + 0.5))
+        );
+        Ok(())
+    }
+
+    fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        self.set_stroke_color(color);
+        push_line!(
+            self.content, "1 w {} {} {} {} re S",
+            number(position.x as f32 + 0.5), number(self.flip(position.y as f32 + h as f32 - 0.5)),
+            w.saturating_sub(1), h.saturating_sub(1)
+        );
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        self.set_fill_color(color);
+        push_line!(
+            self.content, "{} {} {} {} re f",
+            position.x, number(self.flip((position.y + h as i32) as f32)), w, h
+        );
+        Ok(())
+    }
+
+    fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), GrapherError> {
+        self.set_stroke_color(color);
+        self.content.push_str("1 w\n");
+        self.circle_path((position.x as f32, position.y as f32), radius as f32);
+        self.content.push_str("S\n");
+        Ok(())
+    }
+
+    fn draw_text(&mut self, fonts: &mut FontManager, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError> {
+        self.draw_text_rotated(fonts, text, (position.x as f32, position.y as f32), color, size, 0.0)
+    }
+
+    fn draw_text_rotated(&mut self, _fonts: &mut FontManager, text: &str, position: (f32, f32), color: Color, size: u16, rotation: f32) -> Result<(), GrapherError> {
+        self.set_fill_color(color);
+        // Glyphs are addressed by two byte codes, so characters outside the BMP are drawn as a replacement glyph
+        let text: String = text.chars()
+            .map(|c| if (c as u32) > 0xFFFF { char::REPLACEMENT_CHARACTER } else { c })
+            .collect();
+        // The same choice of font as FontManager makes, so the text matches what is drawn on screen
+        let font = if self.fonts[0].covers(&text) { &mut self.fonts[0] } else { &mut self.fonts[1] };
+        let mut encoded = String::new();
+        for character in text.chars() {
+            font.used_characters.insert(character);
+            encoded.push_str(&format!("{:04X}", character as u32));
+        }
+        // Positioned by the baseline, SDL positions text by the top of the line
+        let ascent = font.metrics.ascent as f32 / font.metrics.units_per_em * size as f32;
+        let resource = font.resource;
+        let (sin, cos) = rotation.sin_cos();
+        let baseline = (position.0 + ascent * sin, position.1 + ascent * cos);
+        push_line!(
+            self.content, "BT /{} {} Tf {} {} {} {} {} {} Tm <{}> Tj ET",
+            resource, size, number(cos), number(sin), number(-sin), number(cos),
+            number(baseline.0), number(self.flip(baseline.1)), encoded
+        );
+        Ok(())
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], closed: bool, style: &StrokeStyle) -> Result<(), GrapherError> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        self.content.push_str("q\n");
+        self.set_stroke_style(style);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            push_line!(self.content, "{} {} {}", number(x + 0.5), number(self.flip(y + 0.5)), if i == 0 { "m" } else { "l" });
+        }
+        self.content.push_str(if closed { "h S\nQ\n" } else { "S\nQ\n" });
+        Ok(())
+    }
+
+    fn stroke_circle(&mut self, center: (f32, f32), radius: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.content.push_str("q\n");
+        self.set_stroke_style(style);
+        self.circle_path(center, radius);
+        self.content.push_str("S\nQ\n");
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) -> Result<(), GrapherError> {
+        if points.len() < 3 {
+            return Ok(());
+        }
+        self.set_fill_color(color);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            push_line!(self.content, "{} {} {}", number(x + 0.5), number(self.flip(y + 0.5)), if i == 0 { "m" } else { "l" });
+        }
+        self.content.push_str("h f\n");
+        Ok(())
+    }
+
+    fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: Color) -> Result<(), GrapherError> {
+        self.set_fill_color(color);
+        self.circle_path(center, radius);
+        self.content.push_str("f\n");
+        Ok(())
+    }
+
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.content.push_str("q\n");
+        self.set_stroke_style(style);
+        self.path(path);
+        self.content.push_str("S\nQ\n");
+        Ok(())
+    }
+
+    fn fill_path(&mut self, path: &Path, color: Color) -> Result<(), GrapherError> {
+        self.set_fill_color(color);
+        self.path(path);
+        self.content.push_str("f\n");
+        Ok(())
+    }
+
+    fn set_view(&mut self, origin: (i32, i32), clip: Option<Rect>) -> Result<(), GrapherError> {
+        if self.view.is_some() {
+            self.content.push_str("Q\n");
+        }
+        self.view = if origin == (0, 0) && clip.is_none() { None } else { Some((origin, clip)) };
+        self.open_view();
+        Ok(())
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences(document: &[u8], needle: &str) -> usize {
+        document.windows(needle.len()).filter(|window| *window == needle.as_bytes()).count()
+    }
+
+    fn export(labels: &[&str]) -> Vec<u8> {
+        let mut fonts = FontManager::new();
+        let mut backend = PdfBackend::new(200, 100).unwrap();
+        for label in labels {
+            backend.clear(Color::WHITE);
+            backend.draw_text(&mut fonts, label, PixelCoordinate2D::new(10, 10), Color::BLACK, 12).unwrap();
+            backend.present();
+        }
+        backend.to_pdf()
+    }
+
+    #[test]
+    fn exports_one_page_per_frame() {
+        let document = export(&["x = 1", "y = 2"]);
+        assert!(document.starts_with(b"%PDF"));
+        assert_eq!(occurrences(&document, "/Count 2"), 1);
+        assert_eq!(occurrences(&document, "/FontFile2"), 1);
+    }
+
+    #[test]
+    fn embeds_the_fallback_font_only_for_greek_letters() {
+        assert_eq!(occurrences(&export(&["x = 1", "y = 2"]), "DejaVuSansMono"), 0);
+        let document = export(&["x = 1", "σ = 2"]);
+        assert!(occurrences(&document, "DejaVuSansMono") > 0);
+        assert_eq!(occurrences(&document, "/FontFile2"), 2);
+    }
+
+    #[test]
+    fn characters_outside_the_bmp_are_replaced() {
+        let document = export(&["x\u{1D70B}"]);
+        assert_eq!(occurrences(&document, "<0078FFFD>"), 1);
+    }
+}
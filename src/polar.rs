@@ -0,0 +1,385 @@
+use std::f32::consts::{PI, TAU};
+use sdl2::pixels::Color;
+use crate::colors;
+use crate::coordinate::PixelCoordinate2D;
+use crate::function::drawable_runs;
+use crate::renderer::{Drawable, Renderer};
+use crate::stroke::StrokeStyle;
+use crate::text::{HorizontalAnchor, TextStyle, VerticalAnchor};
+use crate::ticks::Ticks;
+use crate::error::GrapherError;
+
+const LABEL_SIZE: u16 = 14;
+const LABEL_MARGIN: f32 = 6.0;
+// Roughly how many pixels apart the rings should be, the nice step picked decides the exact spacing
+const RING_SPACING: f32 = 60.0;
+// Curves are sampled this often per turn, enough for a smooth outline at any size that fits a window
+const SAMPLES_PER_TURN: f32 = 720.0;
+// How see-through the sectors of a rose plot are filled
+const SECTOR_ALPHA: u8 = 96;
+
+/// Axes around a center point, placing things by their distance `r` from it and angle `θ` in radians.
+/// Angles start from `zero_angle` and go counterclockwise unless the axis is set to clockwise,
+/// negative distances end up on the opposite side like they do for polar functions.
+pub struct PolarAxis {
+    center: PixelCoordinate2D,
+    /// The radius of the outer ring in pixels
+    radius: f32,
+    r_max: f32,
+    zero_angle: f32,
+    clockwise: bool,
+    spokes: usize,
+    unit: AngleUnit,
+    objects: Vec<Box<dyn DrawablePolar>>
+}
+
+pub trait DrawablePolar {
+    fn draw(&self, renderer: &mut Renderer, polar: &PolarAxis) -> Result<(), GrapherError>;
+}
+
+/// How the angles at the spokes are labelled.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AngleUnit {
+    Degrees,
+    /// As fractions of π
+    Radians
+}
+
+impl PolarAxis {
+    /// An axis showing distances up to `r_max` in a circle of `radius` pixels, with 0 pointing right.
+    pub fn new(center: PixelCoordinate2D, radius: f32, r_max: f32) -> PolarAxis {
+        PolarAxis {
+            center,
+            radius,
+            r_max,
+            zero_angle: 0.0,
+            clockwise: false,
+            spokes: 12,
+            unit: AngleUnit::Degrees,
+            objects: Vec::new()
+        }
+    }
+
+    /// Where angle 0 points, in radians counterclockwise from the right, and which way angles grow.
+    /// Compass style plots like antenna patterns use `(PI / 2.0, true)`, zero up and growing clockwise.
+    pub fn set_orientation(&mut self, zero_angle: f32, clockwise: bool) {
+        self.zero_angle = zero_angle;
+        self.clockwise = clockwise;
+    }
+
+    /// How many lines are drawn out from the center, evenly spaced around the circle.
+    pub fn set_spokes(&mut self, spokes: usize) {
+        self.spokes = spokes;
+    }
+
+    pub fn set_angle_unit(&mut self, unit: AngleUnit) {
+        self.unit = unit;
+    }
+
+    /// The distance shown at the outer ring.
+    pub fn set_r_max(&mut self, r_max: f32) {
+        self.r_max = r_max;
+    }
+
+    pub fn r_max(&self) -> f32 {
+        self.r_max
+    }
+
+    pub fn center(&self) -> PixelCoordinate2D {
+        self.center
+    }
+
+    /// Pixels per unit of distance.
+    pub fn scale(&self) -> f32 {
+        if self.r_max == 0.0 { 0.0 } else { self.radius / self.r_max }
+    }
+
+    /// The direction an angle points on screen, in radians counterclockwise from the right like `Renderer::stroke_arc` takes.
+    pub fn screen_angle(&self, theta: f32) -> f32 {
+        if self.clockwise { self.zero_angle - theta } else { self.zero_angle + theta }
+    }
+
+    pub fn to_pixel(&self, r: f32, theta: f32) -> PixelCoordinate2D {
+        let (x, y) = self.to_pixel_precise(r, theta);
+        PixelCoordinate2D::new(x.round() as i32, y.round() as i32)
+    }
+
+    /// The unrounded position of `to_pixel`, for curves that are stroked with antialiasing.
+    pub fn to_pixel_precise(&self, r: f32, theta: f32) -> (f32, f32) {
+        let (sin, cos) = self.screen_angle(theta).sin_cos();
+        let distance = r * self.scale();
+        (self.center.x as f32 + distance * cos, self.center.y as f32 - distance * sin)
+    }
+
+    /// The distance and angle of a pixel, the angle between 0 and 2π.
+    pub fn to_polar(&self, position: PixelCoordinate2D) -> (f32, f32) {
+        let dx = (position.x - self.center.x) as f32;
+        let dy = (self.center.y - position.y) as f32;
+        let screen = dy.atan2(dx);
+        let theta = if self.clockwise { self.zero_angle - screen } else { screen - self.zero_angle };
+        let r = if self.scale() == 0.0 { 0.0 } else { dx.hypot(dy) / self.scale() };
+        (r, theta.rem_euclid(TAU))
+    }
+
+    /// The distances the rings are drawn at, spaced to suit the size of the axis.
+    pub fn r_ticks(&self) -> Ticks {
+        Ticks::new(0.0, self.r_max, (self.radius / RING_SPACING).max(2.0) as usize)
+    }
+
+    pub fn add_object(&mut self, object: Box<dyn DrawablePolar>) {
+        self.objects.push(object);
+    }
+
+    pub fn add_objects<I>(&mut self, objects: I) where I: IntoIterator<Item = Box<dyn DrawablePolar>> {
+        for object in objects {
+            self.add_object(object);
+        }
+    }
+
+    /// The label for the angle of the `index`th spoke.
+    fn angle_label(&self, index: usize) -> String {
+        match self.unit {
+            AngleUnit::Degrees => {
+                let degrees = 360.0 * index as f32 / self.spokes as f32;
+                if degrees.fract() == 0.0 {
+                    format!("{}\\degree", degrees)
+                } else {
+                    format!("{:.1}\\degree", degrees)
+                }
+            },
+            AngleUnit::Radians => {
+                // The angle is 2 index / spokes of π, written as a reduced fraction
+                let (numerator, denominator) = (2 * index, self.spokes);
+                let divisor = gcd(numerator, denominator);
+                match (numerator / divisor, denominator / divisor) {
+                    (0, _) => "0".to_string(),
+                    (1, 1) => "\\pi".to_string(),
+                    (numerator, 1) => format!("{}\\pi", numerator),
+                    (1, denominator) => format!("\\frac{{\\pi}}{{{}}}", denominator),
+                    (numerator, denominator) => format!("\\frac{{{}\\pi}}{{{}}}", numerator, denominator)
+                }
+            }
+        }
+    }
+
+    fn draw_grid(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
+        let center = (self.center.x as f32, self.center.y as f32);
+        let ticks = self.r_ticks();
+        for &r in ticks.values.iter().filter(|&&r| r > 0.0 && r < self.r_max) {
+            renderer.stroke_circle(self.center, r * self.scale(), &theme.major_grid.stroke())?;
+        }
+        for i in 0..self.spokes {
+            let theta = TAU * i as f32 / self.spokes as f32;
+            renderer.stroke_polyline(&[center, self.to_pixel_precise(self.r_max, theta)], &theme.major_grid.stroke())?;
+        }
+        renderer.stroke_circle(self.center, self.radius, &StrokeStyle::new(theme.foreground, 1.0))?;
+
+        // Distances are written along the middle of the first gap between spokes, where no spoke crosses them
+        let style = TextStyle::new(theme.text, LABEL_SIZE);
+        let label_angle = if self.spokes > 0 { PI / self.spokes as f32 } else { 0.0 };
+        for &r in ticks.values.iter().filter(|&&r| r > 0.0) {
+            renderer.draw_text_styled(
+                &ticks.label(r),
+                self.to_pixel(r, label_angle),
+                &style.clone().with_anchor(HorizontalAnchor::Left, VerticalAnchor::Bottom)
+            )?;
+        }
+        for i in 0..self.spokes {
+            let theta = TAU * i as f32 / self.spokes as f32;
+            let (sin, cos) = self.screen_angle(theta).sin_cos();
+            // Anchored on the side facing the center so labels sit just outside the ring all the way round
+            let horizontal = if cos > 0.3 { HorizontalAnchor::Left } else if cos < -0.3 { HorizontalAnchor::Right } else { HorizontalAnchor::Center };
+            let vertical = if sin > 0.3 { VerticalAnchor::Bottom } else if sin < -0.3 { VerticalAnchor::Top } else { VerticalAnchor::Middle };
+            let distance = self.radius + LABEL_MARGIN;
+            renderer.draw_text_styled(
+                &self.angle_label(i),
+                PixelCoordinate2D::new(
+                    (self.center.x as f32 + distance * cos).round() as i32,
+                    (self.center.y as f32 - distance * sin).round() as i32
+                ),
+                &style.clone().with_anchor(horizontal, vertical).with_math()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a.max(1) } else { gcd(b, a % b) }
+}
+
+impl Drawable for PolarAxis {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        self.draw_grid(renderer)?;
+        for object in &self.objects {
+            object.draw(renderer, self)?;
+        }
+        Ok(())
+    }
+}
+
+/// A curve given by its distance from the center at each angle, like a rose or an antenna pattern.
+pub struct PolarFunction {
+    function: Box<dyn Fn(f32) -> f32>,
+    stroke: StrokeStyle,
+    range: (f32, f32)
+}
+
+impl PolarFunction {
+    pub fn new(function: Box<dyn Fn(f32) -> f32>, color: Color) -> PolarFunction {
+        PolarFunction { function, stroke: StrokeStyle::new(color, 1.0), range: (0.0, TAU) }
+    }
+
+    /// The angles the curve is drawn between, once around by default. Spirals and curves that
+    /// take more than one turn to close need a longer range.
+    pub fn with_range(mut self, start: f32, end: f32) -> PolarFunction {
+        self.range = (start, end);
+        self
+    }
+
+    /// Replaces the one pixel line in the constructor's color, for a thicker or dashed curve.
+    pub fn with_stroke(mut self, stroke: StrokeStyle) -> PolarFunction {
+        self.stroke = stroke;
+        self
+    }
+}
+
+impl DrawablePolar for PolarFunction {
+    fn draw(&self, renderer: &mut Renderer, polar: &PolarAxis) -> Result<(), GrapherError> {
+        let (start, end) = self.range;
+        let samples = ((end - start).abs() / TAU * SAMPLES_PER_TURN).ceil().max(1.0) as usize;
+        let runs = drawable_runs((0..=samples).map(|i| {
+            let theta = start + (end - start) * i as f32 / samples as f32;
+            polar.to_pixel_precise((self.function)(theta), theta)
+        }));
+        for run in runs {
+            renderer.stroke_polyline(&run, &self.stroke)?;
+        }
+        Ok(())
+    }
+}
+
+/// A histogram wrapped around the circle, each sector reaching out as far as its value.
+/// The sectors split the circle evenly, the first one starting at angle 0.
+pub struct RosePlot {
+    values: Vec<f32>,
+    color: Color
+}
+
+impl RosePlot {
+    pub fn new(values: Vec<f32>, color: Color) -> RosePlot {
+        RosePlot { values, color }
+    }
+
+    /// Counts how many of the angles, in radians, fall into each of `bins` sectors.
+    pub fn from_angles(angles: &[f32], bins: usize, color: Color) -> RosePlot {
+        let mut values = vec![0.0; bins];
+        if bins > 0 {
+            for angle in angles.iter().filter(|angle| angle.is_finite()) {
+                let bin = (angle.rem_euclid(TAU) / TAU * bins as f32) as usize;
+                values[bin.min(bins - 1)] += 1.0;
+            }
+        }
+        RosePlot::new(values, color)
+    }
+
+    /// Like `from_angles` for directions without a sense, like stress or fracture directions,
+    /// where an angle and the one opposite it are the same. Each is counted on both sides.
+    pub fn from_axial_angles(angles: &[f32], bins: usize, color: Color) -> RosePlot {
+        let both: Vec<f32> = angles.iter().flat_map(|&angle| [angle, angle + PI]).collect();
+        RosePlot::from_angles(&both, bins, color)
+    }
+
+    /// The longest sector, for sizing the axis to fit.
+    pub fn max(&self) -> f32 {
+        self.values.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+impl DrawablePolar for RosePlot {
+    fn draw(&self, renderer: &mut Renderer, polar: &PolarAxis) -> Result<(), GrapherError> {
+        let width = TAU / self.values.len() as f32;
+        let fill = colors::with_alpha(self.color, SECTOR_ALPHA);
+        for (i, &value) in self.values.iter().enumerate() {
+            if value <= 0.0 {
+                continue;
+            }
+            let (start, end) = (polar.screen_angle(i as f32 * width), polar.screen_angle((i + 1) as f32 * width));
+            let radius = value * polar.scale();
+            renderer.fill_wedge(polar.center(), radius, start, end, fill)?;
+            renderer.stroke_wedge(polar.center(), radius, start, end, &StrokeStyle::new(self.color, 1.0))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::RED;
+    use crate::renderer::tests::with_headless_renderer;
+
+    fn axis() -> PolarAxis {
+        PolarAxis::new(PixelCoordinate2D::new(100, 100), 50.0, 2.0)
+    }
+
+    #[test]
+    fn zero_points_right_and_angles_go_counterclockwise() {
+        let polar = axis();
+        assert_eq!(polar.to_pixel(2.0, 0.0), PixelCoordinate2D::new(150, 100));
+        assert_eq!(polar.to_pixel(2.0, PI / 2.0), PixelCoordinate2D::new(100, 50));
+        assert_eq!(polar.to_pixel(1.0, PI), PixelCoordinate2D::new(75, 100));
+        assert_eq!(polar.to_pixel(0.0, 1.0), polar.center());
+        // Negative distances land on the opposite side
+        assert_eq!(polar.to_pixel(-2.0, PI / 2.0), PixelCoordinate2D::new(100, 150));
+
+        let (r, theta) = polar.to_polar(PixelCoordinate2D::new(100, 50));
+        assert!((r - 2.0).abs() < 1e-5 && (theta - PI / 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn compass_orientation_puts_zero_up_and_turns_clockwise() {
+        let mut polar = axis();
+        polar.set_orientation(PI / 2.0, true);
+        assert_eq!(polar.to_pixel(2.0, 0.0), PixelCoordinate2D::new(100, 50));
+        assert_eq!(polar.to_pixel(2.0, PI / 2.0), PixelCoordinate2D::new(150, 100));
+        let (_, theta) = polar.to_polar(PixelCoordinate2D::new(150, 100));
+        assert!((theta - PI / 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn radian_labels_are_reduced_fractions_of_pi() {
+        let mut polar = axis();
+        polar.set_angle_unit(AngleUnit::Radians);
+        polar.set_spokes(4);
+        let labels: Vec<String> = (0..4).map(|i| polar.angle_label(i)).collect();
+        assert_eq!(labels, ["0", "\\frac{\\pi}{2}", "\\pi", "\\frac{3\\pi}{2}"]);
+        polar.set_spokes(8);
+        assert_eq!(polar.angle_label(3), "\\frac{3\\pi}{4}");
+        assert_eq!(polar.angle_label(4), "\\pi");
+        polar.set_spokes(1);
+        assert_eq!(polar.angle_label(1), "2\\pi");
+
+        polar.set_angle_unit(AngleUnit::Degrees);
+        polar.set_spokes(8);
+        assert_eq!(polar.angle_label(1), "45\\degree");
+        polar.set_spokes(7);
+        assert_eq!(polar.angle_label(1), "51.4\\degree");
+    }
+
+    #[test]
+    fn rose_plot_without_values() {
+        let empty = RosePlot::new(Vec::new(), RED);
+        assert_eq!(empty.max(), 0.0);
+        assert!(RosePlot::from_angles(&[0.5, 1.0], 0, RED).values.is_empty());
+
+        let rose = RosePlot::from_angles(&[0.1, 0.2, PI + 0.1, f32::NAN], 4, RED);
+        assert_eq!(rose.values, [2.0, 0.0, 1.0, 0.0]);
+        assert_eq!(RosePlot::from_axial_angles(&[0.1], 4, RED).values, [1.0, 0.0, 1.0, 0.0]);
+
+        with_headless_renderer(200, 200, |renderer| {
+            empty.draw(renderer, &axis()).unwrap();
+        });
+    }
+}
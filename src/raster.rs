@@ -0,0 +1,265 @@
+//! Rasterization helpers shared by the pixel backends.
+//! Every function hands pixels to `plot` as `(x, y, coverage)` where coverage is between 0 and 1.
+
+fn fpart(x: f32) -> f32 {
+    x - x.floor()
+}
+
+fn rfpart(x: f32) -> f32 {
+    1.0 - fpart(x)
+}
+
+/// Cuts a line down to the part inside `size`, with a pixel to spare on every side for anti-aliased edges.
+/// `None` when nothing of it is inside or an endpoint is not a finite number.
+pub fn clip_line(start: (f32, f32), end: (f32, f32), size: (u32, u32)) -> Option<((f32, f32), (f32, f32))> {
+    if ![start.0, start.1, end.0, end.1].iter().all(|value| value.is_finite()) {
+        return None;
+    }
+    // Liang-Barsky, narrowing the part of the line from t = 0 to 1 that is inside each edge in turn
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (min_x, min_y, max_x, max_y) = (-1.0, -1.0, size.0 as f32 + 1.0, size.1 as f32 + 1.0);
+    let (mut enter, mut leave) = (0.0f32, 1.0f32);
+    for (p, q) in [(-dx, start.0 - min_x), (dx, max_x - start.0), (-dy, start.1 - min_y), (dy, max_y - start.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                enter = enter.max(t);
+            } else {
+                leave = leave.min(t);
+            }
+        }
+    }
+    if enter > leave {
+        return None;
+    }
+    Some((
+        (start.0 + enter * dx, start.1 + enter * dy),
+        (start.0 + leave * dx, start.1 + leave * dy)
+    ))
+}
+
+/// Xiaolin Wu's line algorithm, endpoints are in pixel space and may fall between pixels.
+/// The line is clipped to `size` first, so far away or infinite endpoints cost nothing.
+pub fn wu_line<F: FnMut(i32, i32, f32)>(start: (f32, f32), end: (f32, f32), size: (u32, u32), mut plot: F) {
+    let Some((start, end)) = clip_line(start, end, size) else {
+        return;
+    };
+    let (mut x0, mut y0) = start;
+    let (mut x1, mut y1) = end;
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+    let mut plot = |x: i32, y: i32, coverage: f32| {
+        if steep {
+            plot(y, x, coverage);
+        } else {
+            plot(x, y, coverage);
+        }
+    };
+
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+    // First endpoint
+    let x_end = x0.round();
+    let y_end = y0 + gradient * (x_end - x0);
+    let x_gap = rfpart(x0 + 0.5);
+    let x_start = x_end as i32;
+    plot(x_start, y_end.floor() as i32, rfpart(y_end) * x_gap);
+    plot(x_start, y_end.floor() as i32 + 1, fpart(y_end) * x_gap);
+    let mut intery = y_end + gradient;
+
+    // Second endpoint
+    let x_end = x1.round();
+    let y_end = y1 + gradient * (x_end - x1);
+    let x_gap = fpart(x1 + 0.5);
+    let x_stop = x_end as i32;
+    plot(x_stop, y_end.floor() as i32, rfpart(y_end) * x_gap);
+    plot(x_stop, y_end.floor() as i32 + 1, fpart(y_end) * x_gap);
+
+    for x in x_start + 1..x_stop {
+        plot(x, intery.floor() as i32, rfpart(intery));
+        plot(x, intery.floor() as i32 + 1, fpart(intery));
+        intery += gradient;
+    }
+}
+
+/// Wu style circle outline, each octant is walked along its major axis and split between the two closest pixels.
+pub fn wu_circle<F: FnMut(i32, i32, f32)>(center: (f32, f32), radius: f32, mut plot: F) {
+    let (cx, cy) = center;
+    let end = (radius / std::f32::consts::SQRT_2).ceil() as i32;
+    for i in 0..=end {
+        let major = i as f32;
+        let minor = (radius * radius - major * major).max(0.0).sqrt();
+        if minor < major {
+            break;
+        }
+        for (sx, sy) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+            // On the axes both signs of the major offset land on the same pixels
+            if i != 0 || sx > 0.0 {
+                let x = (cx + sx * major).round() as i32;
+                let y = cy + sy * minor;
+                plot(x, y.floor() as i32, rfpart(y));
+                plot(x, y.floor() as i32 + 1, fpart(y));
+            }
+            if i != 0 || sy > 0.0 {
+                let y = (cy + sy * major).round() as i32;
+                let x = cx + sx * minor;
+                plot(x.floor() as i32, y, rfpart(x));
+                plot(x.floor() as i32 + 1, y, fpart(x));
+            }
+        }
+    }
+}
+
+struct Edge {
+    top: (f32, f32),
+    bottom: (f32, f32),
+    winding: i32
+}
+
+/// Fills any number of polygons together using the nonzero winding rule, so overlapping pieces are only covered once.
+/// Without anti-aliasing a pixel is filled when its center is inside, with it every pixel row is sampled on
+/// several sub-scanlines and the horizontal coverage is exact. Nothing outside of `size` is plotted.
+pub fn fill_polygons<F: FnMut(i32, i32, f32)>(polygons: &[Vec<(f32, f32)>], antialias: bool, size: (u32, u32), mut plot: F) {
+    let mut edges: Vec<Edge> = polygons.iter()
+        .flat_map(|polygon| (0..polygon.len()).map(move |i| (polygon[i], polygon[(i + 1) % polygon.len()])))
+        .filter(|(a, b)| a.1 != b.1 && a.0.is_finite() && a.1.is_finite() && b.0.is_finite() && b.1.is_finite())
+        .map(|(a, b)| if a.1 < b.1 {
+            Edge { top: a, bottom: b, winding: 1 }
+        } else {
+            Edge { top: b, bottom: a, winding: -1 }
+        })
+        .collect();
+    if edges.is_empty() {
+        return;
+    }
+    edges.sort_by(|a, b| a.top.1.total_cmp(&b.top.1));
+
+    let (width, height) = (size.0 as i32, size.1 as i32);
+    let min_x = edges.iter().map(|edge| edge.top.0.min(edge.bottom.0)).fold(f32::MAX, f32::min).floor().max(0.0) as i32;
+    let max_x = edges.iter().map(|edge| edge.top.0.max(edge.bottom.0)).fold(f32::MIN, f32::max).ceil().min(width as f32) as i32;
+    let min_y = edges[0].top.1.floor().max(0.0) as i32;
+    let max_y = edges.iter().map(|edge| edge.bottom.1).fold(f32::MIN, f32::max).ceil().min(height as f32) as i32;
+    if min_x >= max_x || min_y >= max_y {
+        return;
+    }
+    let samples = if antialias { 4 } else { 1 };
+    let weight = 1.0 / samples as f32;
+
+    let mut coverage = vec![0.0f32; (max_x - min_x) as usize];
+    let mut active: Vec<&Edge> = Vec::new();
+    let mut next_edge = 0;
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for row in min_y..max_y {
+        coverage.iter_mut().for_each(|c| *c = 0.0);
+        for sample in 0..samples {
+            let y = row as f32 + (sample as f32 + 0.5) * weight;
+            while next_edge < edges.len() && edges[next_edge].top.1 <= y {
+                active.push(&edges[next_edge]);
+                next_edge += 1;
+            }
+            active.retain(|edge| edge.bottom.1 > y);
+
+            crossings.clear();
+            for edge in active.iter().filter(|edge| edge.top.1 <= y) {
+                let t = (y - edge.top.1) / (edge.bottom.1 - edge.top.1);
+                crossings.push((edge.top.0 + t * (edge.bottom.0 - edge.top.0), edge.winding));
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            let mut span_start = 0.0;
+            for &(x, direction) in &crossings {
+                let before = winding;
+                winding += direction;
+                if before == 0 && winding != 0 {
+                    span_start = x;
+                } else if before != 0 && winding == 0 {
+                    add_span(&mut coverage, min_x, span_start, x, antialias, weight);
+                }
+            }
+        }
+        for (i, &c) in coverage.iter().enumerate() {
+            if c > 0.0 {
+                plot(min_x + i as i32, row, c.min(1.0));
+            }
+        }
+    }
+}
+
+/// Adds one sub-scanline span to the coverage of the pixels `min_x` up to `min_x + coverage.len()`, anything past them is cut off.
+fn add_span(coverage: &mut [f32], min_x: i32, start: f32, end: f32, antialias: bool, weight: f32) {
+    let max_x = min_x + coverage.len() as i32;
+    let start = start.clamp(min_x as f32, max_x as f32);
+    let end = end.clamp(min_x as f32, max_x as f32);
+    if antialias {
+        for x in start.floor() as i32..end.ceil() as i32 {
+            let overlap = end.min(x as f32 + 1.0) - start.max(x as f32);
+            if overlap > 0.0 {
+                coverage[(x - min_x) as usize] += overlap * weight;
+            }
+        }
+    } else {
+        // Pixels whose centers fall inside the span
+        for x in (start - 0.5).ceil() as i32..(end - 0.5).ceil() as i32 {
+            coverage[(x - min_x) as usize] = 1.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_line_rejects_non_finite_endpoints() {
+        assert_eq!(clip_line((0.0, 0.0), (f32::INFINITY, 5.0), (10, 10)), None);
+        assert_eq!(clip_line((f32::NAN, 0.0), (5.0, 5.0), (10, 10)), None);
+    }
+
+    #[test]
+    fn clip_line_keeps_lines_inside() {
+        assert_eq!(clip_line((1.0, 2.0), (8.0, 3.0), (10, 10)), Some(((1.0, 2.0), (8.0, 3.0))));
+        assert_eq!(clip_line((20.0, 20.0), (30.0, 25.0), (10, 10)), None);
+    }
+
+    #[test]
+    fn clip_line_cuts_far_endpoints_to_the_margin() {
+        let (start, end) = clip_line((5.0, 5.0), (1e9, 5.0), (10, 10)).unwrap();
+        assert_eq!(start, (5.0, 5.0));
+        assert_eq!(end, (11.0, 5.0));
+    }
+
+    #[test]
+    fn wu_line_far_endpoint_plots_only_near_pixels() {
+        let mut count = 0;
+        wu_line((0.0, 0.0), (1e12, 1e11), (20, 20), |x, y, _| {
+            assert!((-1..=22).contains(&x) && (-1..=22).contains(&y), "({}, {}) is far outside", x, y);
+            count += 1;
+        });
+        assert!(count > 0 && count < 100);
+    }
+
+    #[test]
+    fn fill_polygons_stays_inside_the_size() {
+        let square = vec![(-3.5, -3.5), (12.5, -3.5), (12.5, 9.5), (-3.5, 9.5)];
+        for antialias in [false, true] {
+            let mut pixels = Vec::new();
+            fill_polygons(&[square.clone()], antialias, (10, 6), |x, y, coverage| pixels.push((x, y, coverage)));
+            assert_eq!(pixels.len(), 60);
+            assert!(pixels.iter().all(|&(x, y, coverage)| (0..10).contains(&x) && (0..6).contains(&y) && coverage == 1.0));
+        }
+    }
+}
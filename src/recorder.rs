@@ -0,0 +1,216 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::error::GrapherError;
+use crate::export;
+
+/// Lower is a better palette but slower to encode, 10 is what the gif crate recommends for speed.
+const GIF_QUANTIZATION_SPEED: i32 = 10;
+
+pub enum RecordingFormat {
+    /// A single looping animated GIF
+    Gif,
+    /// One PNG per frame in a directory, named by frame number so they sort in order
+    PngSequence
+}
+
+enum Output {
+    Gif(gif::Encoder<BufWriter<File>>),
+    PngSequence(PathBuf)
+}
+
+/// Captures frames as they are presented, see `Renderer::start_recording`.
+pub struct Recorder {
+    format: RecordingFormat,
+    path: PathBuf,
+    frame_rate: u32,
+    frame_step: u32,
+    output: Option<Output>,
+    size: (u32, u32),
+    presented: u32,
+    frames: u32,
+    error: Option<GrapherError>
+}
+
+impl Recorder {
+    /// Records to `path`, a file for GIFs and a directory for PNG sequences, which is created if missing.
+    /// The frame rate is how fast the frames play back.
+    pub fn new<P: AsRef<Path>>(format: RecordingFormat, path: P, frame_rate: u32) -> Recorder {
+        Recorder {
+            format,
+            path: path.as_ref().to_path_buf(),
+            frame_rate: frame_rate.max(1),
+            frame_step: 1,
+            output: None,
+            size: (0, 0),
+            presented: 0,
+            frames: 0,
+            error: None
+        }
+    }
+
+    pub fn gif<P: AsRef<Path>>(path: P, frame_rate: u32) -> Recorder {
+        Recorder::new(RecordingFormat::Gif, path, frame_rate)
+    }
+
+    pub fn png_sequence<P: AsRef<Path>>(directory: P, frame_rate: u32) -> Recorder {
+        Recorder::new(RecordingFormat::PngSequence, directory, frame_rate)
+    }
+
+    /// Only keeps every `step`th presented frame, so something drawn at 60 frames a second
+    /// can be recorded at 20 with a step of 3 and still play back at the same speed.
+    pub fn with_frame_step(mut self, step: u32) -> Recorder {
+        self.frame_step = step.max(1);
+        self
+    }
+
+    /// How many frames have been written so far.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Called with every presented frame, `read_frame` is only called for frames that are kept
+    /// since reading pixels back is not free. Errors are kept until `finish` so presenting never fails.
+    pub(crate) fn capture<F>(&mut self, read_frame: F)
+    where F: FnOnce() -> Result<((u32, u32), Vec<u8>), GrapherError> {
+        let keep = self.error.is_none() && self.presented.is_multiple_of(self.frame_step);
+        self.presented += 1;
+        if keep {
+            if let Err(error) = read_frame().and_then(|((width, height), mut pixels)| self.write_frame(width, height, &mut pixels)) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    fn open(&mut self, width: u32, height: u32) -> Result<Output, GrapherError> {
+        match self.format {
+            RecordingFormat::Gif => {
+                let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+                    return Err(GrapherError::InvalidData(format!("{}x{} is too large for a GIF", width, height)));
+                };
+                let file = BufWriter::new(File::create(&self.path)?);
+                let mut encoder = gif::Encoder::new(file, gif_width, gif_height, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Ok(Output::Gif(encoder))
+            }
+            RecordingFormat::PngSequence => {
+                std::fs::create_dir_all(&self.path)?;
+                Ok(Output::PngSequence(self.path.clone()))
+            }
+        }
+    }
+
+    fn write_frame(&mut self, width: u32, height: u32, pixels: &mut [u8]) -> Result<(), GrapherError> {
+        if self.output.is_none() {
+            self.output = Some(self.open(width, height)?);
+            self.size = (width, height);
+        } else if self.size != (width, height) {
+            return Err(GrapherError::InvalidData(format!(
+                "Frame size changed from {}x{} to {}x{} while recording", self.size.0, self.size.1, width, height
+            )));
+        }
+        let index = self.frames;
+        match self.output.as_mut() {
+            Some(Output::Gif(encoder)) => {
+                let mut frame = gif::Frame::from_rgba_speed(width as u16, height as u16, pixels, GIF_QUANTIZATION_SPEED);
+                // GIF delays are whole hundredths of a second, spread the rounding so the total time stays right
+                let elapsed = |frame: u32| (frame as u64 * 100 + self.frame_rate as u64 / 2) / self.frame_rate as u64;
+                frame.delay = (elapsed(index + 1) - elapsed(index)) as u16;
+                encoder.write_frame(&frame)?;
+            }
+            Some(Output::PngSequence(directory)) => {
+                export::write_png(directory.join(format!("frame_{:05}.png", index)), width, height, pixels)?;
+            }
+            None => {}
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Writes out whatever is still buffered and reports the first error hit while recording.
+    pub fn finish(mut self) -> Result<u32, GrapherError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        if let Some(Output::Gif(encoder)) = self.output.take() {
+            encoder.into_inner()?.flush()?;
+        }
+        Ok(self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::BufReader;
+    use sdl2::pixels::Color;
+    use super::*;
+    use crate::coordinate::PixelCoordinate2D;
+    use crate::renderer::tests::with_headless_renderer;
+    use crate::renderer::Renderer;
+
+    const WIDTH: u32 = 6;
+    const HEIGHT: u32 = 4;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("grapher-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Presents frames filled with a red that counts up, so the recorded ones can be told apart.
+    fn present_frames(renderer: &mut Renderer, count: u8) {
+        for i in 0..count {
+            renderer.draw_fill_rect(PixelCoordinate2D::origin(), WIDTH, HEIGHT, Color::RGB(i * 20, 0, 0)).unwrap();
+            renderer.present();
+        }
+    }
+
+    #[test]
+    fn png_sequence_keeps_every_frame_step() {
+        let directory = temp_path("png-sequence");
+        with_headless_renderer(WIDTH, HEIGHT, |renderer| {
+            renderer.start_recording(Recorder::png_sequence(&directory, 20).with_frame_step(3));
+            present_frames(renderer, 7);
+            assert_eq!(renderer.stop_recording().unwrap(), 3);
+        });
+
+        let mut names: Vec<String> = fs::read_dir(&directory).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["frame_00000.png", "frame_00001.png", "frame_00002.png"]);
+        // Presented frames 0, 3 and 6 are the ones kept
+        for (name, presented) in names.iter().zip([0, 3, 6]) {
+            let mut reader = png::Decoder::new(BufReader::new(File::open(directory.join(name)).unwrap())).read_info().unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+            reader.next_frame(&mut pixels).unwrap();
+            assert_eq!(reader.info().size(), (WIDTH, HEIGHT));
+            assert_eq!(pixels[..4], [presented * 20, 0, 0, 255], "{}", name);
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn gif_decodes_with_every_frame_and_its_delay() {
+        let path = temp_path("recording.gif");
+        with_headless_renderer(WIDTH, HEIGHT, |renderer| {
+            renderer.start_recording(Recorder::gif(&path, 30));
+            present_frames(renderer, 4);
+            assert_eq!(renderer.stop_recording().unwrap(), 4);
+        });
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (WIDTH as u16, HEIGHT as u16));
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        // A thirtieth of a second does not fit in hundredths, the rounding is spread so four frames still take 13
+        assert_eq!(delays, [3, 4, 3, 3]);
+        fs::remove_file(&path).unwrap();
+    }
+}
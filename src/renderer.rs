@@ -1,50 +1,267 @@
 use sdl2::pixels::Color;
-use sdl2::render::WindowCanvas;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
 use sdl2::{EventPump, Sdl};
 use sdl2::video::Window;
+use crate::backend::{Antialiasing, Backend, SdlBackend};
 use crate::coordinate::PixelCoordinate2D;
+use crate::font::FontManager;
+use crate::recorder::Recorder;
+use crate::path::Path;
+use crate::stroke::{self, DashPattern, StrokeStyle};
+use crate::text::{self, TextStyle};
+use crate::theme::Theme;
+use crate::error::GrapherError;
 
 pub struct Renderer {
-    pub(crate) canvas: WindowCanvas,
+    pub(crate) backend: Box<dyn Backend>,
+    pub(crate) fonts: FontManager,
+    antialiasing: Antialiasing,
+    views: Vec<View>,
+    recorder: Option<Recorder>,
+    theme: Theme,
     pub sdl: Sdl,
     pub event_pump: EventPump
 }
 
+/// An entry on the clip and viewport stack, in pixels of the whole output.
+struct View {
+    origin: (i32, i32),
+    clip: Rect,
+    kind: ViewKind
+}
+
+#[derive(PartialEq)]
+enum ViewKind {
+    Clip,
+    /// Holds the size drawing is confined to
+    Viewport(u32, u32),
+    Translation
+}
+
 impl Renderer {
-    pub fn new(window: Window, sdl: Sdl) -> Result<Renderer, String> {
-        let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
-        let mut event_pump = sdl.event_pump()?;
-        Ok(Renderer { canvas, sdl, event_pump})
+    pub fn new(window: Window, sdl: Sdl) -> Result<Renderer, GrapherError> {
+        let canvas = window.into_canvas().build()?;
+        let mut event_pump = sdl.event_pump().map_err(GrapherError::Sdl)?;
+        Ok(Renderer { backend: Box::new(SdlBackend::window(canvas)), fonts: FontManager::new(), antialiasing: Antialiasing::Off, views: Vec::new(), recorder: None, theme: Theme::default(), sdl, event_pump })
+    }
+
+    /// Creates a renderer backed by a software surface of the given size instead of a window.
+    /// Nothing is shown on screen, use `read_pixels` to get the rendered frame back.
+    pub fn new_headless(width: u32, height: u32, sdl: Sdl) -> Result<Renderer, GrapherError> {
+        let backend = SdlBackend::offscreen(width, height)?;
+        let event_pump = sdl.event_pump().map_err(GrapherError::Sdl)?;
+        Ok(Renderer { backend: Box::new(backend), fonts: FontManager::new(), antialiasing: Antialiasing::Off, views: Vec::new(), recorder: None, theme: Theme::default(), sdl, event_pump })
+    }
+
+    /// Switches every following line and circle between aliased and anti-aliased rasterization.
+    pub fn set_antialiasing(&mut self, mode: Antialiasing) {
+        self.antialiasing = mode;
+        self.backend.set_antialiasing(mode);
+    }
+
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.backend.window_id().is_none()
+    }
+
+    /// The id of the window being drawn to, `None` when headless.
+    pub fn window_id(&self) -> Option<u32> {
+        self.backend.window_id()
+    }
+
+    pub fn size(&self) -> Result<(u32, u32), GrapherError> {
+        self.backend.size()
+    }
+
+    /// Reads back the whole frame as tightly packed RGBA bytes, row by row from the top left.
+    pub fn read_pixels(&self) -> Result<Vec<u8>, GrapherError> {
+        self.backend.read_pixels(None)
+    }
+
+    pub fn read_pixel(&self, position: PixelCoordinate2D) -> Result<Color, GrapherError> {
+        let pixel = self.backend.read_pixels(Some(Rect::new(position.x, position.y, 1, 1)))?;
+        Ok(Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Temporarily swaps in another backend while `draw` runs and hands the backend back afterwards.
+    pub fn with_backend<F>(&mut self, backend: Box<dyn Backend>, draw: F) -> Result<Box<dyn Backend>, GrapherError>
+    where F: FnOnce(&mut Renderer) -> Result<(), GrapherError> {
+        let previous = std::mem::replace(&mut self.backend, backend);
+        // Frames presented to another backend, like PDF pages, are not part of the recording
+        let recorder = self.recorder.take();
+        self.backend.set_antialiasing(self.antialiasing);
+        let result = self.apply_view().and_then(|_| draw(self));
+        let used = std::mem::replace(&mut self.backend, previous);
+        self.recorder = recorder;
+        result.map(|_| used)
+    }
+
+    /// Temporarily swaps in another backend, clears it, draws a single object into it and hands the backend back.
+    pub fn draw_with_backend(&mut self, backend: Box<dyn Backend>, object: &dyn Drawable) -> Result<Box<dyn Backend>, GrapherError> {
+        self.with_backend(backend, |renderer| {
+            renderer.clear();
+            object.draw(renderer)
+        })
+    }
+
+    /// Draws a single object into a fresh offscreen frame of the given size and returns its RGBA pixels.
+    /// The object is scaled so that it fills the frame the same way it fills this renderer.
+    pub fn render_offscreen(&mut self, object: &dyn Drawable, width: u32, height: u32) -> Result<Vec<u8>, GrapherError> {
+        let (logical_width, logical_height) = self.size()?;
+        let mut offscreen = SdlBackend::offscreen(width, height)?;
+        offscreen.set_scale(width as f32 / logical_width as f32, height as f32 / logical_height as f32)?;
+        let offscreen = self.draw_with_backend(Box::new(offscreen), object)?;
+        offscreen.read_pixels(None)
+    }
+
+    /// Hides anything drawn outside of the rectangle until `pop_clip`, on top of any clip already in place.
+    pub fn push_clip(&mut self, position: PixelCoordinate2D, w: u32, h: u32) -> Result<(), GrapherError> {
+        let origin = self.origin();
+        let clip = self.clip_within(Rect::new(position.x + origin.0, position.y + origin.1, w, h))?;
+        self.views.push(View { origin, clip, kind: ViewKind::Clip });
+        self.apply_view()
+    }
+
+    pub fn pop_clip(&mut self) -> Result<(), GrapherError> {
+        match self.views.last() {
+            Some(view) if view.kind == ViewKind::Clip => {
+                self.views.pop();
+                self.apply_view()
+            }
+            _ => Err(GrapherError::InvalidData("There is no clip rectangle to pop".to_string()))
+        }
+    }
+
+    /// Confines drawing to the rectangle and moves the origin to its top left corner until `pop_viewport`,
+    /// so anything that draws from (0, 0) can be placed in a part of the window.
+    pub fn push_viewport(&mut self, position: PixelCoordinate2D, w: u32, h: u32) -> Result<(), GrapherError> {
+        let origin = self.origin();
+        let origin = (position.x + origin.0, position.y + origin.1);
+        let clip = self.clip_within(Rect::new(origin.0, origin.1, w, h))?;
+        self.views.push(View { origin, clip, kind: ViewKind::Viewport(w, h) });
+        self.apply_view()
+    }
+
+    pub fn pop_viewport(&mut self) -> Result<(), GrapherError> {
+        match self.views.last() {
+            Some(view) if matches!(view.kind, ViewKind::Viewport(..)) => {
+                self.views.pop();
+                self.apply_view()
+            }
+            _ => Err(GrapherError::InvalidData("There is no viewport to pop".to_string()))
+        }
+    }
+
+    /// The size of the innermost viewport, or of the whole output when there is none.
+    pub fn viewport_size(&self) -> Result<(u32, u32), GrapherError> {
+        match self.views.iter().rev().find_map(|view| match view.kind {
+            ViewKind::Viewport(w, h) => Some((w, h)),
+            _ => None
+        }) {
+            Some(size) => Ok(size),
+            None => self.size()
+        }
+    }
+
+    /// Moves everything drawn until `pop_translation` by the offset, without clipping it.
+    pub fn push_translation(&mut self, x: i32, y: i32) -> Result<(), GrapherError> {
+        let (width, height) = self.size()?;
+        let origin = self.origin();
+        let clip = self.views.last().map_or(Rect::new(0, 0, width, height), |view| view.clip);
+        self.views.push(View { origin: (origin.0 + x, origin.1 + y), clip, kind: ViewKind::Translation });
+        self.apply_view()
+    }
+
+    pub fn pop_translation(&mut self) -> Result<(), GrapherError> {
+        match self.views.last() {
+            Some(view) if view.kind == ViewKind::Translation => {
+                self.views.pop();
+                self.apply_view()
+            }
+            _ => Err(GrapherError::InvalidData("There is no translation to pop".to_string()))
+        }
+    }
+
+    fn origin(&self) -> (i32, i32) {
+        self.views.last().map_or((0, 0), |view| view.origin)
+    }
+
+    fn clip_within(&self, rect: Rect) -> Result<Rect, GrapherError> {
+        let (width, height) = self.size()?;
+        let bounds = self.views.last().map_or(Rect::new(0, 0, width, height), |view| view.clip);
+        // SDL rectangles can not be empty, so nothing being visible is a single pixel off the top left
+        Ok(rect.intersection(bounds).unwrap_or(Rect::new(-1, -1, 1, 1)))
+    }
+
+    fn apply_view(&mut self) -> Result<(), GrapherError> {
+        match self.views.last() {
+            Some(view) => {
+                // A clip covering the whole output does nothing, leaving it out keeps vector output simpler
+                let (width, height) = self.backend.size()?;
+                let clip = Some(view.clip).filter(|&clip| clip != Rect::new(0, 0, width, height));
+                self.backend.set_view(view.origin, clip)
+            }
+            None => self.backend.set_view((0, 0), None)
+        }
     }
 
     pub fn clear(&mut self) {
-        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
-        self.canvas.clear();
+        self.backend.clear(self.theme.background);
+    }
+
+    /// Sets the colors used by `clear` and every built in drawable from the next frame on.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
     }
 
     pub fn present(&mut self) {
-        self.canvas.present();
+        // Read before presenting, a window's back buffer is undefined afterwards
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.capture(|| Ok((self.size()?, self.read_pixels()?)));
+            self.recorder = Some(recorder);
+        }
+        self.backend.present();
     }
 
-    pub fn draw_dot(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.draw_point(sdl2::rect::Point::new(position.x, position.y)).unwrap();
-        Ok(())
+    /// Captures every frame passed to `present` from now on, replacing any recording already running.
+    /// Works the same for a window and a headless renderer.
+    pub fn start_recording(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
     }
 
-    pub fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.draw_rect(sdl2::rect::Rect::new(position.x, position.y, w, h)).unwrap();
-        Ok(())
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
     }
 
-    pub fn draw_fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.fill_rect(sdl2::rect::Rect::new(position.x, position.y, w, h)).unwrap();
-        Ok(())
+    /// Stops capturing and finishes writing the recording, returns how many frames it holds.
+    pub fn stop_recording(&mut self) -> Result<u32, GrapherError> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Err(GrapherError::InvalidData("Nothing is being recorded".to_string()))
+        }
     }
 
-    // pub fn draw_function(&mut self, f: fn(i32, i32) -> (i32, i32), color: Color) -> Result<(), String> {
+    pub fn draw_dot(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_point(position, color)
+    }
+
+    pub fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_rect(position, w, h, color)
+    }
+
+    pub fn draw_fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_rect(position, w, h, color)
+    }
+
+    // pub fn draw_function(&mut self, f: fn(i32, i32) -> (i32, i32), color: Color) -> Result<(), GrapherError> {
     //     for x in 0..800 {
     //         let (x, y) = f(x, 400);
     //         self.draw_dot(x, y, color)?;
@@ -52,43 +269,20 @@ impl Renderer {
     //     Ok(())
     // }
 
-    pub fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        // Draw a circle as a series of dots
-        let mut x = 0;
-        let mut y = radius as i32;
-        let mut d = 3 - 2 * radius as i32;
-        while x <= y {
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x + x, position.y + y)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x + x, position.y - y)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x - x, position.y + y)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x - x, position.y - y)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x + y, position.y + x)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x + y, position.y - x)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x - y, position.y + x)).unwrap();
-            self.canvas.draw_point(sdl2::rect::Point::new(position.x - y, position.y - x)).unwrap();
-            if d < 0 {
-                d += 4 * x + 6;
-            } else {
-                d += 4 * (x - y) + 10;
-                y -= 1;
-            }
-            x += 1;
-        }
-        Ok(())
+    pub fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_circle(position, radius, color)
     }
 
-    pub fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position1.x, position1.y),
-            sdl2::rect::Point::new(position2.x, position2.y)
-        ).unwrap();
-        Ok(())
+    pub fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_line(position1, position2, color)
     }
 
-    pub fn draw_dotted_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color, step: u32) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
+    /// Draws a line between positions that fall between pixels, smooth when anti-aliasing is on.
+    pub fn draw_line_precise(&mut self, position1: (f32, f32), position2: (f32, f32), color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_line_precise(position1, position2, color)
+    }
+
+    pub fn draw_dotted_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color, step: u32) -> Result<(), GrapherError> {
         let mut x = position1.x;
         let mut y = position1.y;
         let dx = position2.x - position1.x;
@@ -97,36 +291,21 @@ impl Renderer {
         let x_inc = dx as f32 / steps as f32;
         let y_inc = dy as f32 / steps as f32;
         for _ in 0..steps {
-            self.canvas.draw_point(sdl2::rect::Point::new(x, y)).unwrap();
+            self.backend.draw_point(PixelCoordinate2D::new(x, y), color)?;
             x += x_inc as i32 * step as i32;
             y += y_inc as i32 * step as i32;
         }
         Ok(())
     }
 
-    pub fn draw_triangle(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, position3: PixelCoordinate2D, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position1.x, position1.y),
-            sdl2::rect::Point::new(position2.x, position2.y)
-        ).unwrap();
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position2.x, position2.y),
-            sdl2::rect::Point::new(position3.x, position3.y)
-        ).unwrap();
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position3.x, position3.y),
-            sdl2::rect::Point::new(position1.x, position1.y)
-        ).unwrap();
-        Ok(())
+    pub fn draw_triangle(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, position3: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_line(position1, position2, color)?;
+        self.backend.draw_line(position2, position3, color)?;
+        self.backend.draw_line(position3, position1, color)
     }
 
-    pub fn draw_arrow(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position1.x, position1.y),
-            sdl2::rect::Point::new(position2.x, position2.y)
-        ).unwrap();
+    pub fn draw_arrow(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.backend.draw_line(position1, position2, color)?;
         // Draw the arrowhead as a triangle
         let arrow_size = 10;
         let dx = position2.x - position1.x;
@@ -140,75 +319,310 @@ impl Renderer {
         let y1 = position2.y as f32 - arrow_size as f32 * angle1.sin();
         let x2 = position2.x as f32 - arrow_size as f32 * angle2.cos();
         let y2 = position2.y as f32 - arrow_size as f32 * angle2.sin();
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position2.x, position2.y),
-            sdl2::rect::Point::new(x1 as i32, y1 as i32)
-        ).unwrap();
-        self.canvas.draw_line(
-            sdl2::rect::Point::new(position2.x, position2.y),
-            sdl2::rect::Point::new(x2 as i32, y2 as i32)
-        ).unwrap();
-        Ok(())
+        self.backend.draw_line(position2, PixelCoordinate2D::new(x1 as i32, y1 as i32), color)?;
+        self.backend.draw_line(position2, PixelCoordinate2D::new(x2 as i32, y2 as i32), color)
     }
 
 
 
 
-    // TODO: I want to be able to specify a starting point that the dashes will propagate out from
-    pub fn draw_dashed_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color, blank_step: u32, dash_step: u32) -> Result<(), String> {
-        self.canvas.set_draw_color(color);
-        let mut x = position1.x;
-        let mut y = position1.y;
-        let dx = position2.x - position1.x;
-        let dy = position2.y - position1.y;
-        let horizontal = dx.abs() > dy.abs();
-        let steps = if horizontal {
-            dx.abs() as u32 / (blank_step + dash_step) * 2
-        } else {
-            dy.abs() as u32 / (blank_step + dash_step) * 2
-        };
-        let mut blank = true;
-        let mut last = position1;
-        for _ in 0..steps {
-            if blank {
-                x += if horizontal { blank_step as i32 } else { 0 };
-                y += if horizontal { 0 } else { blank_step as i32 };
-            } else {
-                x += if horizontal { dash_step as i32 } else { 0 };
-                y += if horizontal { 0 } else { dash_step as i32 };
-                self.canvas.draw_line(
-                    sdl2::rect::Point::new(last.x, last.y),
-                    sdl2::rect::Point::new(x, y)
-                ).unwrap();
-            }
-            last = PixelCoordinate2D::new(x, y);
-            blank = !blank;
+    /// Dashes start at `position1`, see `draw_dashed_line_anchored` to line them up with some other point.
+    pub fn draw_dashed_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color, blank_step: u32, dash_step: u32) -> Result<(), GrapherError> {
+        self.draw_dashed_line_anchored(position1, position2, color, blank_step, dash_step, position1)
+    }
+
+    /// Draws a dashed line in any direction with a dash starting at `anchor` and the pattern propagating out from it,
+    /// so lines that move along themselves (like a crosshair) keep their dashes in place relative to the anchor.
+    /// The anchor does not need to be on the line, it is projected onto it.
+    pub fn draw_dashed_line_anchored(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color, blank_step: u32, dash_step: u32, anchor: PixelCoordinate2D) -> Result<(), GrapherError> {
+        let start = to_precise(position1);
+        let end = to_precise(position2);
+        let length = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+        if length == 0.0 {
+            return Ok(());
+        }
+        let direction = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+        let phase = (start.0 - anchor.x as f32) * direction.0 + (start.1 - anchor.y as f32) * direction.1;
+        let pattern = DashPattern::new(&[dash_step as f32, blank_step as f32], phase);
+        for dash in stroke::dash_polyline(&[start, end], false, &pattern) {
+            self.backend.draw_line_precise(dash[0], dash[dash.len() - 1], color)?;
         }
         Ok(())
     }
 
-    pub fn draw_object(&mut self, object: &dyn Drawable) -> Result<(), String> {
+    pub fn stroke_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(&[to_precise(position1), to_precise(position2)], false, style)
+    }
+
+    /// Strokes connected line segments through positions that may fall between pixels,
+    /// corners are joined with the style's join instead of overlapping separate lines.
+    pub fn stroke_polyline(&mut self, points: &[(f32, f32)], style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(points, false, style)
+    }
+
+    /// Like `stroke_polyline` with the last point joined back to the first.
+    pub fn stroke_polygon(&mut self, points: &[(f32, f32)], style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(points, true, style)
+    }
+
+    /// The stroke is centered on the same pixels `draw_rect` would draw.
+    pub fn stroke_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        let (x, y) = to_precise(position);
+        let (right, bottom) = (x + w.saturating_sub(1) as f32, y + h.saturating_sub(1) as f32);
+        self.backend.stroke_polyline(&[(x, y), (right, y), (right, bottom), (x, bottom)], true, style)
+    }
+
+    pub fn stroke_triangle(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, position3: PixelCoordinate2D, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(&[to_precise(position1), to_precise(position2), to_precise(position3)], true, style)
+    }
+
+    pub fn stroke_circle(&mut self, position: PixelCoordinate2D, radius: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_circle(to_precise(position), radius, style)
+    }
+
+    /// Fills the inside of a polygon, concave and self-intersecting outlines are filled with the nonzero winding rule.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_polygon(points, color)
+    }
+
+    pub fn fill_triangle(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, position3: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_polygon(&[to_precise(position1), to_precise(position2), to_precise(position3)], color)
+    }
+
+    pub fn fill_circle(&mut self, position: PixelCoordinate2D, radius: f32, color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_circle(to_precise(position), radius, color)
+    }
+
+    /// `rotation` is in radians and turns the x radius clockwise on screen.
+    pub fn stroke_ellipse(&mut self, position: PixelCoordinate2D, radius_x: f32, radius_y: f32, rotation: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(&stroke::ellipse_polygon(to_precise(position), radius_x, radius_y, rotation), true, style)
+    }
+
+    pub fn fill_ellipse(&mut self, position: PixelCoordinate2D, radius_x: f32, radius_y: f32, rotation: f32, color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_polygon(&stroke::ellipse_polygon(to_precise(position), radius_x, radius_y, rotation), color)
+    }
+
+    /// Strokes the part of a circle between two angles in radians, measured counterclockwise from the positive x direction.
+    pub fn stroke_arc(&mut self, position: PixelCoordinate2D, radius: f32, start_angle: f32, end_angle: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_polyline(&stroke::arc_points(to_precise(position), radius, start_angle, end_angle), false, style)
+    }
+
+    /// Fills the pie slice between two angles, see `stroke_arc` for how they are measured.
+    pub fn fill_wedge(&mut self, position: PixelCoordinate2D, radius: f32, start_angle: f32, end_angle: f32, color: Color) -> Result<(), GrapherError> {
+        let mut points = vec![to_precise(position)];
+        points.extend(stroke::arc_points(to_precise(position), radius, start_angle, end_angle));
+        self.backend.fill_polygon(&points, color)
+    }
+
+    /// Outlines the pie slice between two angles, see `stroke_arc` for how they are measured.
+    pub fn stroke_wedge(&mut self, position: PixelCoordinate2D, radius: f32, start_angle: f32, end_angle: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        let mut points = vec![to_precise(position)];
+        points.extend(stroke::arc_points(to_precise(position), radius, start_angle, end_angle));
+        self.backend.stroke_polyline(&points, true, style)
+    }
+
+    /// Strokes a path of straight and Bezier segments, see `Path`.
+    pub fn stroke_path(&mut self, path: &Path, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.backend.stroke_path(path, style)
+    }
+
+    /// Fills a path with the nonzero winding rule, so subpaths wound the other way cut holes.
+    pub fn fill_path(&mut self, path: &Path, color: Color) -> Result<(), GrapherError> {
+        self.backend.fill_path(path, color)
+    }
+
+    /// The arrowhead grows with the line width and is always drawn solid.
+    pub fn stroke_arrow(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, style: &StrokeStyle) -> Result<(), GrapherError> {
+        self.stroke_line(position1, position2, style)?;
+        let arrow_size = 10.0 + style.width * 2.0;
+        let angle = ((position2.y - position1.y) as f32).atan2((position2.x - position1.x) as f32);
+        let angle1 = angle + std::f32::consts::PI / 6.0;
+        let angle2 = angle - std::f32::consts::PI / 6.0;
+        let tip = to_precise(position2);
+        let head = StrokeStyle { dash: None, ..style.clone() };
+        self.backend.stroke_polyline(&[
+            (tip.0 - arrow_size * angle1.cos(), tip.1 - arrow_size * angle1.sin()),
+            tip,
+            (tip.0 - arrow_size * angle2.cos(), tip.1 - arrow_size * angle2.sin())
+        ], false, &head)
+    }
+
+    pub fn draw_object(&mut self, object: &dyn Drawable) -> Result<(), GrapherError> {
         object.draw(self)
     }
 
-    pub fn draw_all_objects(&mut self, objects: &Vec<&dyn Drawable>) -> Result<(), String> {
+    pub fn draw_all_objects(&mut self, objects: &Vec<&dyn Drawable>) -> Result<(), GrapherError> {
         for object in objects {
             object.draw(self)?;
         }
         Ok(())
     }
 
-    pub fn draw_text(&mut self, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), String> {
-        let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
-        let font = ttf_context.load_font("assets/JetBrainsMono.ttf", size).map_err(|e| e.to_string())?;
-        let surface = font.render(text).blended(color).map_err(|e| e.to_string())?;
-        let texture_creator = self.canvas.texture_creator();
-        let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
-        self.canvas.copy(&texture, None, sdl2::rect::Rect::new(position.x, position.y, surface.width(), surface.height())).map_err(|e| e.to_string())?;
+    /// Draws text with its top left corner at the position, each line of multi-line text below the last.
+    pub fn draw_text(&mut self, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError> {
+        if !text.contains('\n') {
+            return self.backend.draw_text(&mut self.fonts, text, position, color, size);
+        }
+        self.draw_text_styled(text, position, &TextStyle::new(color, size))
+    }
+
+    /// Draws text anchored, aligned and rotated around the position following the style.
+    pub fn draw_text_styled(&mut self, text: &str, position: PixelCoordinate2D, style: &TextStyle) -> Result<(), GrapherError> {
+        let (x, y) = to_precise(position);
+        let layout = text::layout(&mut self.fonts, text, style)?;
+        for run in &layout.runs {
+            let run_position = (x + run.offset.0, y + run.offset.1);
+            if style.rotation == 0.0 {
+                self.backend.draw_text(
+                    &mut self.fonts,
+                    &run.text,
+                    PixelCoordinate2D::new(run_position.0.round() as i32, run_position.1.round() as i32),
+                    style.color,
+                    run.size
+                )?;
+            } else {
+                self.backend.draw_text_rotated(&mut self.fonts, &run.text, run_position, style.color, run.size, style.rotation)?;
+            }
+        }
+        for rule in &layout.rules {
+            // Rules are measured from pixel edges like the text, strokes go through pixel centers
+            let start = (x + rule.start.0 - 0.5, y + rule.start.1 - 0.5);
+            let end = (x + rule.end.0 - 0.5, y + rule.end.1 - 0.5);
+            self.backend.stroke_polyline(&[start, end], false, &StrokeStyle::new(style.color, rule.width))?;
+        }
         Ok(())
     }
+
+    /// Draws a label written in math markup, like `\sigma_1` or `\frac{x^2}{2}`, see the `math` module.
+    pub fn draw_math(&mut self, markup: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError> {
+        self.draw_text_styled(markup, position, &TextStyle::new(color, size).with_math())
+    }
+
+    /// The width and height of the block of text before any rotation, including every line.
+    pub fn text_size(&mut self, text: &str, size: u16) -> Result<(u32, u32), GrapherError> {
+        self.measure_text(text, &TextStyle::new(Color::WHITE, size))
+    }
+
+    /// Like `text_size`, taking line spacing and math markup from the style.
+    pub fn measure_text(&mut self, text: &str, style: &TextStyle) -> Result<(u32, u32), GrapherError> {
+        let layout = text::layout(&mut self.fonts, text, style)?;
+        Ok((layout.width.ceil() as u32, layout.height.ceil() as u32))
+    }
+}
+
+fn to_precise(position: PixelCoordinate2D) -> (f32, f32) {
+    (position.x as f32, position.y as f32)
 }
 
 pub trait Drawable {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String>;
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError>;
+
+    /// Lets interactive objects react to input, returning true when they need to be drawn again.
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Mutex;
+    use super::*;
+    use crate::colors::RED;
+
+    /// Runs a test against a headless renderer. SDL can only be started from one thread at a time
+    /// and allows a single event pump, so every test that needs one takes its turn here.
+    pub(crate) fn with_headless_renderer<F: FnOnce(&mut Renderer)>(width: u32, height: u32, test: F) {
+        static SDL: Mutex<()> = Mutex::new(());
+        let _turn = SDL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut renderer = Renderer::new_headless(width, height, sdl2::init().unwrap()).unwrap();
+        renderer.set_theme(Theme::light());
+        test(&mut renderer);
+    }
+
+    const WIDTH: u32 = 8;
+    const HEIGHT: u32 = 6;
+
+    fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * WIDTH + x) * 4) as usize;
+        [pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3]]
+    }
+
+    fn rgba(color: Color) -> [u8; 4] {
+        [color.r, color.g, color.b, color.a]
+    }
+
+    #[test]
+    fn headless_pixels() {
+        with_headless_renderer(WIDTH, HEIGHT, check_pixels);
+    }
+
+    fn check_pixels(renderer: &mut Renderer) {
+        let background = rgba(renderer.theme().background);
+
+        assert_eq!(renderer.size().unwrap(), (WIDTH, HEIGHT));
+        assert_eq!(renderer.read_pixels().unwrap().len(), (WIDTH * HEIGHT * 4) as usize);
+
+        renderer.clear();
+        let pixels = renderer.read_pixels().unwrap();
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                assert_eq!(pixel(&pixels, x, y), background, "pixel ({}, {}) after clear", x, y);
+            }
+        }
+
+        renderer.draw_fill_rect(PixelCoordinate2D::new(2, 1), 3, 2, RED).unwrap();
+        let pixels = renderer.read_pixels().unwrap();
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let inside = (2..5).contains(&x) && (1..3).contains(&y);
+                let expected = if inside { rgba(RED) } else { background };
+                assert_eq!(pixel(&pixels, x, y), expected, "pixel ({}, {}) after fill_rect", x, y);
+            }
+        }
+        assert_eq!(renderer.read_pixel(PixelCoordinate2D::new(3, 2)).unwrap(), RED);
+
+        renderer.clear();
+        renderer.draw_line(PixelCoordinate2D::new(1, 4), PixelCoordinate2D::new(6, 4), RED).unwrap();
+        let pixels = renderer.read_pixels().unwrap();
+        // Only the inside of the line is checked, whether SDL draws the last point differs between versions
+        for x in 2..6 {
+            assert_eq!(pixel(&pixels, x, 4), rgba(RED), "pixel ({}, 4) on the line", x);
+            assert_eq!(pixel(&pixels, x, 3), background, "pixel ({}, 3) above the line", x);
+            assert_eq!(pixel(&pixels, x, 5), background, "pixel ({}, 5) below the line", x);
+        }
+        assert_eq!(pixel(&pixels, 0, 4), background);
+        assert_eq!(pixel(&pixels, 7, 4), background);
+    }
+
+    #[test]
+    fn anchored_diagonal_dashes_line_up_across_segments() {
+        with_headless_renderer(40, 40, |renderer| {
+            let anchor = PixelCoordinate2D::origin();
+            let (start, middle, end) = (anchor, PixelCoordinate2D::new(20, 20), PixelCoordinate2D::new(36, 36));
+            let background = renderer.theme().background;
+
+            renderer.clear();
+            renderer.draw_dashed_line_anchored(start, end, RED, 4, 4, anchor).unwrap();
+            let whole = renderer.read_pixels().unwrap();
+            // Dashes are 4 pixels along the line, so they cover the diagonal from 0 to 4 and 8 to 12 but not 4 to 8
+            assert_eq!(renderer.read_pixel(PixelCoordinate2D::new(1, 1)).unwrap(), RED);
+            assert_eq!(renderer.read_pixel(PixelCoordinate2D::new(4, 4)).unwrap(), background);
+            assert_eq!(renderer.read_pixel(PixelCoordinate2D::new(7, 7)).unwrap(), RED);
+
+            // Split in two where the pattern is in a gap, the halves draw exactly the same dashes
+            renderer.clear();
+            renderer.draw_dashed_line_anchored(start, middle, RED, 4, 4, anchor).unwrap();
+            renderer.draw_dashed_line_anchored(middle, end, RED, 4, 4, anchor).unwrap();
+            assert!(renderer.read_pixels().unwrap() == whole, "split dashes do not line up with the whole line");
+
+            // Restarting the pattern at the join instead puts a dash in that gap
+            let in_gap = PixelCoordinate2D::new(21, 21);
+            assert_eq!(renderer.read_pixel(in_gap).unwrap(), background);
+            renderer.clear();
+            renderer.draw_dashed_line(start, middle, RED, 4, 4).unwrap();
+            renderer.draw_dashed_line(middle, end, RED, 4, 4).unwrap();
+            assert_eq!(renderer.read_pixel(in_gap).unwrap(), RED);
+            assert!(renderer.read_pixels().unwrap() != whole);
+        });
+    }
 }
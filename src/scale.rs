@@ -0,0 +1,84 @@
+/// How values along one axis are spread over its pixels. The axis scale and origin are applied after this,
+/// so for the log scales the origin is where the value is 1 and the scale is pixels per power of the base.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Every power of ten takes the same space, only positive values can be shown
+    Log10,
+    /// Like `Log10` with the scale measured per power of e, ticks still land on powers of ten
+    Ln,
+    /// Linear close to zero and logarithmic beyond `threshold` on either side, for data that crosses zero
+    /// but still spans orders of magnitude. The threshold has to be positive
+    SymLog { threshold: f32 }
+}
+
+impl Scale {
+    /// From a value to the space the axis spreads evenly, not a number for values the scale can not show.
+    pub fn forward(self, value: f32) -> f32 {
+        match self {
+            Scale::Linear => value,
+            Scale::Log10 if value > 0.0 => value.log10(),
+            Scale::Ln if value > 0.0 => value.ln(),
+            Scale::Log10 | Scale::Ln => f32::NAN,
+            Scale::SymLog { threshold } => value.signum() * (value.abs() / threshold).ln_1p() / std::f32::consts::LN_10
+        }
+    }
+
+    pub fn inverse(self, value: f32) -> f32 {
+        match self {
+            Scale::Linear => value,
+            Scale::Log10 => 10f32.powf(value),
+            Scale::Ln => value.exp(),
+            Scale::SymLog { threshold } => value.signum() * threshold * (value.abs() * std::f32::consts::LN_10).exp_m1()
+        }
+    }
+
+    /// Whether zero can be shown, the log scales instead put the other axis along their lower edge.
+    pub fn has_zero(self) -> bool {
+        !matches!(self, Scale::Log10 | Scale::Ln)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(scale: Scale, values: &[f32]) {
+        for &value in values {
+            let back = scale.inverse(scale.forward(value));
+            assert!((back - value).abs() <= value.abs() * 1e-5, "{:?} turned {} into {}", scale, value, back);
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_round_trip(Scale::Linear, &[-3.5, 0.0, 2.0, 1e6]);
+        assert_round_trip(Scale::Log10, &[1e-4, 0.5, 1.0, 7.0, 1e8]);
+        assert_round_trip(Scale::Ln, &[1e-4, 0.5, 1.0, 7.0, 1e8]);
+        assert_round_trip(Scale::SymLog { threshold: 1.0 }, &[-1e5, -3.0, 0.0, 0.25, 40.0, 1e5]);
+        assert_round_trip(Scale::SymLog { threshold: 0.01 }, &[-2.0, -0.005, 0.005, 2.0]);
+    }
+
+    #[test]
+    fn log_spaces_decades_evenly() {
+        assert_eq!(Scale::Log10.forward(1.0), 0.0);
+        assert_eq!(Scale::Log10.forward(1000.0), 3.0);
+        assert!((Scale::Ln.forward(std::f32::consts::E) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn log_can_not_show_zero_or_negatives() {
+        assert!(Scale::Log10.forward(0.0).is_nan());
+        assert!(Scale::Ln.forward(-1.0).is_nan());
+        assert!(!Scale::Log10.has_zero());
+        assert!(Scale::SymLog { threshold: 1.0 }.has_zero());
+    }
+
+    #[test]
+    fn symlog_is_symmetric() {
+        let scale = Scale::SymLog { threshold: 2.0 };
+        assert_eq!(scale.forward(0.0), 0.0);
+        assert_eq!(scale.forward(-50.0), -scale.forward(50.0));
+    }
+}
@@ -1,15 +1,48 @@
 use crate::axis::{Axis2D, DrawableAxis2D};
+use crate::colormap::Colormap;
 use crate::coordinate::CartesianCoordinate2D;
+use crate::path::Path;
 use crate::renderer::Renderer;
+use crate::stroke::StrokeStyle;
+use crate::error::GrapherError;
 
 pub struct Scatter2D {
     objects: Vec<CartesianCoordinate2D>,
-    color: sdl2::pixels::Color
+    color: sdl2::pixels::Color,
+    spline: Option<StrokeStyle>,
+    radius: Option<f32>,
+    values: Option<(Vec<f32>, Colormap, (f32, f32))>
 }
 
 impl Scatter2D {
     pub fn new(objects: Vec<CartesianCoordinate2D>, color: sdl2::pixels::Color) -> Scatter2D {
-        Scatter2D { objects, color }
+        Scatter2D { objects, color, spline: None, radius: None, values: None }
+    }
+
+    /// Also draws a smooth curve through the points in the order they were added.
+    pub fn with_spline(mut self, stroke: StrokeStyle) -> Scatter2D {
+        self.spline = Some(stroke);
+        self
+    }
+
+    /// Draws each point as a filled circle instead of a single pixel, overlapping translucent points build up.
+    pub fn with_radius(mut self, radius: f32) -> Scatter2D {
+        self.radius = Some(radius);
+        self
+    }
+
+    /// Colors each point by a value of its own, the `i`th value going with the `i`th point.
+    /// Values are placed on the colormap between the ends of `range`, points without a value keep the plain color.
+    pub fn with_values(mut self, values: Vec<f32>, colormap: Colormap, range: (f32, f32)) -> Scatter2D {
+        self.values = Some((values, colormap, range));
+        self
+    }
+
+    fn color_of(&self, index: usize) -> sdl2::pixels::Color {
+        match &self.values {
+            Some((values, colormap, range)) if index < values.len() => colormap.map(values[index], range.0, range.1),
+            _ => self.color
+        }
     }
 
     pub fn add(&mut self, object: CartesianCoordinate2D) {
@@ -18,10 +51,18 @@ impl Scatter2D {
 }
 
 impl DrawableAxis2D for Scatter2D {
-    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), String> {
-        for object in &self.objects {
+    fn draw(&self, renderer: &mut Renderer, axis2d: &Axis2D) -> Result<(), GrapherError> {
+        if let Some(stroke) = &self.spline {
+            let points: Vec<(f32, f32)> = self.objects.iter().map(|&object| axis2d.to_pixel_precise(object)).collect();
+            renderer.stroke_path(&Path::spline(&points), stroke)?;
+        }
+        for (i, object) in self.objects.iter().enumerate() {
             let position = axis2d.to_pixel(*object);
-            renderer.draw_dot(position, self.color)?;
+            let color = self.color_of(i);
+            match self.radius {
+                Some(radius) => renderer.fill_circle(position, radius, color)?,
+                None => renderer.draw_dot(position, color)?
+            }
         }
         Ok(())
     }
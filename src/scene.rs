@@ -0,0 +1,347 @@
+use sdl2::event::Event;
+use sdl2::rect::Rect;
+use crate::coordinate::PixelCoordinate2D;
+use crate::renderer::{Drawable, Renderer};
+use crate::error::GrapherError;
+
+pub type NodeId = usize;
+
+/// Where a node is drawn relative to where it would draw itself.
+/// Only moving and clipping are supported, since not every backend can scale or rotate text.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Transform {
+    pub offset: (i32, i32),
+    /// Hides anything the node draws outside of this rectangle, measured before the offset is applied
+    pub clip: Option<Rect>
+}
+
+struct Node {
+    id: NodeId,
+    object: Box<dyn Drawable>,
+    z: i32,
+    visible: bool,
+    transform: Transform
+}
+
+struct Layer {
+    name: String,
+    z: i32,
+    visible: bool,
+    nodes: Vec<Node>
+}
+
+/// Objects kept between frames, grouped into named layers that can be hidden and reordered.
+/// Layers and the nodes in them are drawn from the lowest z to the highest, ties keep the order they were added in.
+/// The scene remembers when something changed, so `render` only redraws the frame when it has to.
+pub struct Scene {
+    layers: Vec<Layer>,
+    next_id: NodeId,
+    dirty: bool
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene { layers: Vec::new(), next_id: 0, dirty: true }
+    }
+
+    pub fn add_layer(&mut self, name: &str, z: i32) -> Result<(), GrapherError> {
+        if self.layers.iter().any(|layer| layer.name == name) {
+            return Err(GrapherError::InvalidData(format!("Layer {} already exists", name)));
+        }
+        self.layers.push(Layer { name: name.to_string(), z, visible: true, nodes: Vec::new() });
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn layer_mut(&mut self, name: &str) -> Result<&mut Layer, GrapherError> {
+        self.layers.iter_mut().find(|layer| layer.name == name).ok_or(GrapherError::InvalidData(format!("Layer {} not found", name)))
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, GrapherError> {
+        self.layers.iter_mut()
+            .flat_map(|layer| layer.nodes.iter_mut())
+            .find(|node| node.id == id)
+            .ok_or(GrapherError::InvalidData(format!("Node {} not found", id)))
+    }
+
+    /// Adds an object to the top of a layer and returns the id used to change it later.
+    pub fn add(&mut self, layer: &str, object: Box<dyn Drawable>) -> Result<NodeId, GrapherError> {
+        let id = self.next_id;
+        let layer = self.layer_mut(layer)?;
+        let z = layer.nodes.iter().map(|node| node.z).max().unwrap_or(0);
+        layer.nodes.push(Node { id, object, z, visible: true, transform: Transform::default() });
+        self.next_id += 1;
+        self.dirty = true;
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Result<Box<dyn Drawable>, GrapherError> {
+        for layer in &mut self.layers {
+            if let Some(index) = layer.nodes.iter().position(|node| node.id == id) {
+                self.dirty = true;
+                return Ok(layer.nodes.remove(index).object);
+            }
+        }
+        Err(GrapherError::InvalidData(format!("Node {} not found", id)))
+    }
+
+    /// The object behind a node, changing it marks the scene for redrawing.
+    pub fn object_mut(&mut self, id: NodeId) -> Result<&mut Box<dyn Drawable>, GrapherError> {
+        self.dirty = true;
+        Ok(&mut self.node_mut(id)?.object)
+    }
+
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> Result<(), GrapherError> {
+        self.layer_mut(name)?.visible = visible;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn toggle_layer(&mut self, name: &str) -> Result<(), GrapherError> {
+        let layer = self.layer_mut(name)?;
+        layer.visible = !layer.visible;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn is_layer_visible(&self, name: &str) -> bool {
+        self.layers.iter().any(|layer| layer.name == name && layer.visible)
+    }
+
+    pub fn set_layer_z(&mut self, name: &str, z: i32) -> Result<(), GrapherError> {
+        self.layer_mut(name)?.z = z;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) -> Result<(), GrapherError> {
+        self.node_mut(id)?.visible = visible;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// The order of a node within its layer.
+    pub fn set_z(&mut self, id: NodeId, z: i32) -> Result<(), GrapherError> {
+        self.node_mut(id)?.z = z;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) -> Result<(), GrapherError> {
+        self.node_mut(id)?.transform = transform;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// For changes the scene can not see, like an object that follows the mouse.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Clears, draws and presents the frame if anything changed since the last time, returns whether it did.
+    pub fn render(&mut self, renderer: &mut Renderer) -> Result<bool, GrapherError> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        renderer.clear();
+        self.draw(renderer)?;
+        renderer.present();
+        self.dirty = false;
+        Ok(true)
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene::new()
+    }
+}
+
+// Everything pushed is popped again even when drawing or clipping failed, so the view stack stays balanced
+fn draw_node(node: &Node, renderer: &mut Renderer) -> Result<(), GrapherError> {
+    let Transform { offset, clip } = node.transform;
+    renderer.push_translation(offset.0, offset.1)?;
+    let result = match clip {
+        Some(clip) => draw_clipped(node, clip, renderer),
+        None => node.object.draw(renderer)
+    };
+    let popped = renderer.pop_translation();
+    result.and(popped)
+}
+
+fn draw_clipped(node: &Node, clip: Rect, renderer: &mut Renderer) -> Result<(), GrapherError> {
+    renderer.push_clip(PixelCoordinate2D::new(clip.x(), clip.y()), clip.width(), clip.height())?;
+    let result = node.object.draw(renderer);
+    let popped = renderer.pop_clip();
+    result.and(popped)
+}
+
+impl Drawable for Scene {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let mut layers: Vec<&Layer> = self.layers.iter().filter(|layer| layer.visible).collect();
+        layers.sort_by_key(|layer| layer.z);
+        for layer in layers {
+            let mut nodes: Vec<&Node> = layer.nodes.iter().filter(|node| node.visible).collect();
+            nodes.sort_by_key(|node| node.z);
+            for node in nodes {
+                if node.transform == Transform::default() {
+                    node.object.draw(renderer)?;
+                } else {
+                    draw_node(node, renderer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes the event to every visible node, positions are left in window pixels whatever the node's transform.
+    /// Marks the scene for redrawing when any of them asks for it.
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let mut changed = false;
+        for layer in self.layers.iter_mut().filter(|layer| layer.visible) {
+            for node in layer.nodes.iter_mut().filter(|node| node.visible) {
+                changed |= node.object.handle_event(event);
+            }
+        }
+        self.dirty |= changed;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::*;
+    use crate::renderer::tests::with_headless_renderer;
+
+    type Log = Rc<RefCell<Vec<&'static str>>>;
+
+    /// Writes down when it is drawn instead of drawing anything.
+    struct Recording {
+        name: &'static str,
+        log: Log,
+        fails: bool
+    }
+
+    impl Drawable for Recording {
+        fn draw(&self, _renderer: &mut Renderer) -> Result<(), GrapherError> {
+            self.log.borrow_mut().push(self.name);
+            if self.fails {
+                return Err(GrapherError::InvalidData(format!("{} failed", self.name)));
+            }
+            Ok(())
+        }
+
+        fn handle_event(&mut self, _event: &Event) -> bool {
+            self.name == "interactive"
+        }
+    }
+
+    fn recording(log: &Log, name: &'static str) -> Box<dyn Drawable> {
+        Box::new(Recording { name, log: log.clone(), fails: false })
+    }
+
+    fn drawn(scene: &Scene, renderer: &mut Renderer, log: &Log) -> Vec<&'static str> {
+        log.borrow_mut().clear();
+        scene.draw(renderer).unwrap();
+        log.borrow().clone()
+    }
+
+    #[test]
+    fn draws_layers_and_nodes_by_z() {
+        with_headless_renderer(20, 20, |renderer| {
+            let log = Log::default();
+            let mut scene = Scene::new();
+            scene.add_layer("front", 1).unwrap();
+            scene.add_layer("back", 0).unwrap();
+            scene.add("front", recording(&log, "label")).unwrap();
+            let grid = scene.add("back", recording(&log, "grid")).unwrap();
+            scene.add("back", recording(&log, "curve")).unwrap();
+            assert_eq!(drawn(&scene, renderer, &log), ["grid", "curve", "label"]);
+
+            scene.set_z(grid, 5).unwrap();
+            assert_eq!(drawn(&scene, renderer, &log), ["curve", "grid", "label"]);
+            scene.set_layer_z("back", 2).unwrap();
+            assert_eq!(drawn(&scene, renderer, &log), ["label", "curve", "grid"]);
+        });
+    }
+
+    #[test]
+    fn hidden_nodes_and_layers_are_skipped() {
+        with_headless_renderer(20, 20, |renderer| {
+            let log = Log::default();
+            let mut scene = Scene::new();
+            scene.add_layer("plot", 0).unwrap();
+            scene.add_layer("overlay", 1).unwrap();
+            let curve = scene.add("plot", recording(&log, "curve")).unwrap();
+            scene.add("plot", recording(&log, "points")).unwrap();
+            scene.add("overlay", recording(&log, "legend")).unwrap();
+
+            scene.set_visible(curve, false).unwrap();
+            assert_eq!(drawn(&scene, renderer, &log), ["points", "legend"]);
+
+            scene.toggle_layer("overlay").unwrap();
+            assert!(!scene.is_layer_visible("overlay"));
+            assert_eq!(drawn(&scene, renderer, &log), ["points"]);
+
+            scene.toggle_layer("overlay").unwrap();
+            scene.set_layer_visible("plot", false).unwrap();
+            assert_eq!(drawn(&scene, renderer, &log), ["legend"]);
+            assert!(scene.toggle_layer("missing").is_err());
+        });
+    }
+
+    #[test]
+    fn only_redraws_after_changes() {
+        with_headless_renderer(20, 20, |renderer| {
+            let log = Log::default();
+            let mut scene = Scene::new();
+            scene.add_layer("plot", 0).unwrap();
+            let curve = scene.add("plot", recording(&log, "curve")).unwrap();
+            scene.add("plot", recording(&log, "interactive")).unwrap();
+            assert!(scene.is_dirty());
+            assert!(scene.render(renderer).unwrap());
+            assert!(!scene.is_dirty());
+            assert!(!scene.render(renderer).unwrap());
+            assert_eq!(log.borrow().len(), 2);
+
+            scene.set_transform(curve, Transform { offset: (3, 4), clip: None }).unwrap();
+            assert!(scene.is_dirty());
+            assert!(scene.render(renderer).unwrap());
+
+            // Events only dirty the scene when a node asks to be redrawn
+            assert!(scene.handle_event(&Event::Quit { timestamp: 0 }));
+            assert!(scene.is_dirty());
+            scene.render(renderer).unwrap();
+            scene.set_visible(curve, false).unwrap();
+            scene.render(renderer).unwrap();
+            scene.remove(curve).unwrap();
+            assert!(scene.is_dirty());
+            scene.render(renderer).unwrap();
+            scene.toggle_layer("plot").unwrap();
+            scene.render(renderer).unwrap();
+            assert!(!scene.handle_event(&Event::Quit { timestamp: 0 }));
+            assert!(!scene.is_dirty());
+        });
+    }
+
+    #[test]
+    fn failed_draws_leave_the_view_stack_balanced() {
+        with_headless_renderer(20, 20, |renderer| {
+            let log = Log::default();
+            let mut scene = Scene::new();
+            scene.add_layer("plot", 0).unwrap();
+            let broken = scene.add("plot", Box::new(Recording { name: "broken", log: log.clone(), fails: true })).unwrap();
+            scene.set_transform(broken, Transform { offset: (2, 2), clip: Some(Rect::new(0, 0, 5, 5)) }).unwrap();
+            assert!(scene.draw(renderer).is_err());
+            // Nothing is left to pop, the translation and the clip were both taken off again
+            assert!(renderer.pop_clip().is_err());
+            assert!(renderer.pop_translation().is_err());
+        });
+    }
+}
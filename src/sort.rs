@@ -1,15 +1,17 @@
 use std::time::Duration;
 use crate::axis::Axis2D;
-use crate::colors::{GREEN, PINK, RED, WHITE};
 use crate::{audio, config};
+use crate::colors::{self, Gradient};
 use crate::coordinate::PixelCoordinate2D;
 use crate::renderer::{Drawable, Renderer};
+use crate::error::GrapherError;
 
 const PADDING: u32 = 10;
 const ELEMENT_PADDING: u32 = 3;
 const ELEMENT_WIDTH: u32 = 15;
 const ELEMENT_MAX: u32 = 512;
 const AUDIO_SORT_LOOP: bool = true;
+const HIGHLIGHT_ALPHA: u8 = 48;
 
 pub struct SortingVisualization {
     element_count: u32,
@@ -22,11 +24,12 @@ pub struct SortingVisualization {
     pub(crate) sorted: bool,
     correct: bool,
     sort_type: SortType,
-    pub(crate) auto_sort: bool
+    pub(crate) auto_sort: bool,
+    value_colors: Option<Gradient>
 }
 
 pub trait DrawableSortingVisualization {
-    fn draw(&self, renderer: &mut Renderer, sort_vis: &SortingVisualization) -> Result<(), String>;
+    fn draw(&self, renderer: &mut Renderer, sort_vis: &SortingVisualization) -> Result<(), GrapherError>;
 }
 
 #[derive(Clone, Copy, PartialOrd, PartialEq)]
@@ -53,10 +56,17 @@ impl SortingVisualization {
             sorted: true,
             correct: true,
             sort_type,
-            auto_sort: false
+            auto_sort: false,
+            value_colors: None
         }
     }
 
+    /// Colors each bar by its value instead of the theme's foreground, so the order shows even without the heights.
+    pub fn with_value_colors(mut self, gradient: Gradient) -> SortingVisualization {
+        self.value_colors = Some(gradient);
+        self
+    }
+
     pub fn shuffle(&mut self) {
         use rand::seq::SliceRandom;
         let mut rng = rand::thread_rng();
@@ -70,16 +80,17 @@ impl SortingVisualization {
         self.gap = 0;
     }
 
-    pub fn step(&mut self) {
+    pub fn step(&mut self) -> Result<(), GrapherError> {
         if !self.sorted {
             match self.sort_type {
                 SortType::Insertion => self.stepable_insertion_sort(),
                 SortType::Shell => self.stepable_shell_sort(),
-                SortType::Merge => self.stepable_merge_sort(),
-                SortType::Quick => self.stepable_quick_sort(),
+                SortType::Merge => self.stepable_merge_sort()?,
+                SortType::Quick => self.stepable_quick_sort()?,
                 _ => {}
             }
         }
+        Ok(())
     }
 
     fn set_sorted(&mut self) {
@@ -209,8 +220,8 @@ impl SortingVisualization {
         }
     }
 
-    fn stepable_merge_sort(&mut self) {
-        unimplemented!("Merge sort is not yet implemented");
+    fn stepable_merge_sort(&mut self) -> Result<(), GrapherError> {
+        Err(GrapherError::InvalidData("Merge sort can not be stepped through yet".to_string()))
     }
 
     fn merge_sort(&mut self, elements: &mut Vec<u32>) {
@@ -246,12 +257,27 @@ impl SortingVisualization {
         }
     }
 
-    fn stepable_quick_sort(&mut self) {
-        unimplemented!("Quick sort is not yet implemented");
+    fn stepable_quick_sort(&mut self) -> Result<(), GrapherError> {
+        Err(GrapherError::InvalidData("Quick sort can not be stepped through yet".to_string()))
     }
 
-    fn quick_sort(&mut self, elements: &mut Vec<u32>) {
-        unimplemented!("Quick sort is not yet implemented");
+    fn quick_sort(&mut self, elements: &mut [u32]) {
+        if elements.len() > 1 {
+            // Lomuto partition around the last element
+            let pivot = elements[elements.len() - 1];
+            let mut store = 0;
+            for i in 0..elements.len() - 1 {
+                if elements[i] < pivot {
+                    elements.swap(i, store);
+                    store += 1;
+                }
+            }
+            let last = elements.len() - 1;
+            elements.swap(store, last);
+            let (left, right) = elements.split_at_mut(store);
+            self.quick_sort(left);
+            self.quick_sort(&mut right[1..]);
+        }
     }
 
     fn element_number_to_pixel_x(&self, element_number: u32) -> i32 {
@@ -260,23 +286,32 @@ impl SortingVisualization {
 }
 
 impl Drawable for SortingVisualization {
-    fn draw(&self, renderer: &mut Renderer) -> Result<(), String> {
+    fn draw(&self, renderer: &mut Renderer) -> Result<(), GrapherError> {
+        let theme = *renderer.theme();
         for (i, element) in self.elements.iter().enumerate() {
             let position = PixelCoordinate2D::new(
                 self.element_number_to_pixel_x(i as u32),
                 (config::WINDOW_HEIGHT - *element - PADDING) as i32
             );
             let color = if self.sorted && self.correct {
-                GREEN
+                theme.success
             } else if self.sorted && !self.correct {
-                RED
+                theme.failure
             } else if self.active_elements.contains(&i) {
-                RED
+                theme.highlight
             } else if self.index == i && i != 0 && self.sort_type == SortType::Insertion {
-                PINK
+                theme.accent(0)
+            } else if let Some(gradient) = &self.value_colors {
+                gradient.map(*element as f32, 0.0, ELEMENT_MAX as f32)
             } else {
-                WHITE
+                theme.foreground
             };
+            if self.active_elements.contains(&i) && !self.sorted {
+                // A translucent column behind the whole height so the compared elements stand out
+                let x = self.element_number_to_pixel_x(i as u32);
+                let highlight = colors::with_alpha(theme.highlight, HIGHLIGHT_ALPHA);
+                renderer.draw_fill_rect(PixelCoordinate2D::new(x, PADDING as i32), ELEMENT_WIDTH, config::WINDOW_HEIGHT - PADDING * 2, highlight)?;
+            }
             renderer.draw_rect(position, ELEMENT_WIDTH, *element, color)?;
         }
         renderer.draw_text(
@@ -287,25 +322,25 @@ impl Drawable for SortingVisualization {
                 SortType::Quick => "Quick"
             }),
             PixelCoordinate2D::new(0, 0),
-            WHITE,
+            theme.text,
             20
         )?;
         renderer.draw_text(
             &format!("Elements: {}", self.element_count),
             PixelCoordinate2D::new(0, 25),
-            WHITE,
+            theme.text,
             20
         )?;
         renderer.draw_text(
             &format!("Comparisons: {}", self.comparison_count),
             PixelCoordinate2D::new(0, 50),
-            WHITE,
+            theme.text,
             20
         )?;
         renderer.draw_text(
             &format!("Swaps: {}", self.swap_count),
             PixelCoordinate2D::new(0, 75),
-            WHITE,
+            theme.text,
             20
         )?;
         if self.sorted {
@@ -313,14 +348,14 @@ impl Drawable for SortingVisualization {
                 renderer.draw_text(
                     "Sorted!",
                     PixelCoordinate2D::new(0, 100),
-                    GREEN,
+                    theme.success,
                     20
                 )?;
             } else {
                 renderer.draw_text(
                     "Not Sorted :(",
                     PixelCoordinate2D::new(0, 100),
-                    RED,
+                    theme.failure,
                     20
                 )?;
             }
@@ -0,0 +1,422 @@
+use sdl2::pixels::Color;
+
+// Miters longer than this many half widths are cut off as a bevel
+const MITER_LIMIT: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel
+}
+
+/// Alternating dash and gap lengths in pixels, starting with a dash.
+/// The phase is how far into the pattern the line starts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DashPattern {
+    pub lengths: Vec<f32>,
+    pub phase: f32
+}
+
+impl DashPattern {
+    pub fn new(lengths: &[f32], phase: f32) -> DashPattern {
+        DashPattern { lengths: lengths.to_vec(), phase }
+    }
+
+    /// An odd number of lengths is repeated so dashes and gaps keep alternating, the same as SVG does.
+    pub fn normalized_lengths(&self) -> Vec<f32> {
+        if self.lengths.len() % 2 == 1 {
+            self.lengths.repeat(2)
+        } else {
+            self.lengths.clone()
+        }
+    }
+
+    pub fn period(&self) -> f32 {
+        self.normalized_lengths().iter().sum()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub color: Color,
+    pub dash: Option<DashPattern>,
+    pub cap: LineCap,
+    pub join: LineJoin
+}
+
+impl StrokeStyle {
+    pub fn new(color: Color, width: f32) -> StrokeStyle {
+        StrokeStyle {
+            width,
+            color,
+            dash: None,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter
+        }
+    }
+
+    pub fn with_dash(mut self, dash: DashPattern) -> StrokeStyle {
+        self.dash = Some(dash);
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> StrokeStyle {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> StrokeStyle {
+        self.join = join;
+        self
+    }
+
+    pub fn is_dashed(&self) -> bool {
+        self.dash.as_ref().is_some_and(|dash| dash.period() > 0.0)
+    }
+}
+
+type Point = (f32, f32);
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+/// Splits a polyline into the pieces that fall on the dashes of the pattern.
+/// The pattern carries on around corners instead of restarting at every vertex.
+pub fn dash_polyline(points: &[Point], closed: bool, pattern: &DashPattern) -> Vec<Vec<Point>> {
+    let lengths = pattern.normalized_lengths();
+    let period: f32 = lengths.iter().sum();
+    let mut points = points.to_vec();
+    if closed && !points.is_empty() {
+        points.push(points[0]);
+    }
+    if period <= 0.0 || lengths.iter().any(|&length| length < 0.0) {
+        return vec![points];
+    }
+
+    // Find where in the pattern the line starts
+    let mut index = 0;
+    let mut remaining = lengths[0];
+    let mut offset = pattern.phase.rem_euclid(period);
+    while offset > 0.0 {
+        if offset >= remaining {
+            offset -= remaining;
+            index = (index + 1) % lengths.len();
+            remaining = lengths[index];
+        } else {
+            remaining -= offset;
+            offset = 0.0;
+        }
+    }
+
+    let mut runs = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    if index % 2 == 0 && !points.is_empty() {
+        current.push(points[0]);
+    }
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let length = distance(start, end);
+        let mut travelled = 0.0;
+        while length - travelled > remaining {
+            travelled += remaining;
+            // Either ends the current dash or starts the next one
+            current.push(lerp(start, end, travelled / length));
+            if index % 2 == 0 {
+                runs.push(std::mem::take(&mut current));
+            }
+            index = (index + 1) % lengths.len();
+            remaining = lengths[index];
+        }
+        remaining -= length - travelled;
+        if index % 2 == 0 {
+            current.push(end);
+        }
+    }
+    if index % 2 == 0 && current.len() > 1 {
+        runs.push(current);
+    }
+    runs.retain(|run| run.len() > 1);
+    runs
+}
+
+/// A polygon approximating a circle, with enough sides that the flats are under a pixel long.
+pub fn circle_polygon(center: Point, radius: f32) -> Vec<Point> {
+    let sides = ((std::f32::consts::TAU * radius).ceil() as usize).clamp(8, 720);
+    (0..sides).map(|i| {
+        let angle = std::f32::consts::TAU * i as f32 / sides as f32;
+        (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+    }).collect()
+}
+
+/// A polygon approximating an ellipse rotated by `rotation` radians, with the same density of sides as `circle_polygon`.
+pub fn ellipse_polygon(center: Point, radius_x: f32, radius_y: f32, rotation: f32) -> Vec<Point> {
+    let sides = ((std::f32::consts::TAU * radius_x.max(radius_y)).ceil() as usize).clamp(8, 720);
+    let (sin, cos) = rotation.sin_cos();
+    (0..sides).map(|i| {
+        let angle = std::f32::consts::TAU * i as f32 / sides as f32;
+        let (x, y) = (radius_x * angle.cos(), radius_y * angle.sin());
+        (center.0 + x * cos - y * sin, center.1 + x * sin + y * cos)
+    }).collect()
+}
+
+/// Points along a circular arc from `start` to `end` radians, both ends included.
+/// Angles go counterclockwise on screen like they do on an axis, so positive angles are above the center.
+pub fn arc_points(center: Point, radius: f32, start: f32, end: f32) -> Vec<Point> {
+    let sweep = end - start;
+    let segments = ((radius * sweep.abs()).ceil() as usize).clamp(1, 720);
+    (0..=segments).map(|i| {
+        let angle = start + sweep * i as f32 / segments as f32;
+        (center.0 + radius * angle.cos(), center.1 - radius * angle.sin())
+    }).collect()
+}
+
+fn signed_area(polygon: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    area / 2.0
+}
+
+fn add_polygon(polygons: &mut Vec<Vec<Point>>, mut polygon: Vec<Point>) {
+    // Keeping every piece wound the same way means overlaps fill once under the nonzero rule
+    if signed_area(&polygon) < 0.0 {
+        polygon.reverse();
+    }
+    polygons.push(polygon);
+}
+
+fn direction(a: Point, b: Point) -> Point {
+    let length = distance(a, b);
+    ((b.0 - a.0) / length, (b.1 - a.1) / length)
+}
+
+fn offset(point: Point, normal: Point, amount: f32) -> Point {
+    (point.0 + normal.0 * amount, point.1 + normal.1 * amount)
+}
+
+fn add_join(polygons: &mut Vec<Vec<Point>>, vertex: Point, incoming: Point, outgoing: Point, half: f32, join: LineJoin) {
+    let normal_in = (-incoming.1, incoming.0);
+    let normal_out = (-outgoing.1, outgoing.0);
+    let cross = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+    if cross.abs() < 1e-6 {
+        return;
+    }
+    // The outside of the corner is on the opposite side to the turn
+    let side = if cross > 0.0 { -half } else { half };
+    let outer_in = offset(vertex, normal_in, side);
+    let outer_out = offset(vertex, normal_out, side);
+    match join {
+        LineJoin::Round => add_polygon(polygons, circle_polygon(vertex, half)),
+        LineJoin::Bevel => add_polygon(polygons, vec![vertex, outer_in, outer_out]),
+        LineJoin::Miter => {
+            let bisector = (normal_in.0 + normal_out.0, normal_in.1 + normal_out.1);
+            let bisector_length = (bisector.0.powi(2) + bisector.1.powi(2)).sqrt();
+            let cos_half_angle = bisector_length / 2.0;
+            if bisector_length < 1e-6 || 1.0 / cos_half_angle > MITER_LIMIT {
+                add_polygon(polygons, vec![vertex, outer_in, outer_out]);
+            } else {
+                let miter = offset(
+                    vertex,
+                    (bisector.0 / bisector_length, bisector.1 / bisector_length),
+                    side / cos_half_angle
+                );
+                add_polygon(polygons, vec![vertex, outer_in, miter, outer_out]);
+            }
+        }
+    }
+}
+
+/// Turns a stroked polyline into filled polygons, one per segment, join and cap.
+/// They overlap, so they need to be filled together with the nonzero rule.
+pub fn outline(points: &[Point], closed: bool, style: &StrokeStyle) -> Vec<Vec<Point>> {
+    let half = style.width / 2.0;
+    let mut polygons = Vec::new();
+    if half <= 0.0 {
+        return polygons;
+    }
+    let runs = match &style.dash {
+        Some(dash) if style.is_dashed() => dash_polyline(points, closed, dash),
+        _ => vec![points.to_vec()]
+    };
+    let closed = closed && !style.is_dashed();
+
+    for run in runs {
+        let mut run: Vec<Point> = run.into_iter().fold(Vec::new(), |mut run, point| {
+            if run.last().is_none_or(|&last| distance(last, point) > 1e-4) {
+                run.push(point);
+            }
+            run
+        });
+        if closed && run.len() > 2 && distance(run[0], run[run.len() - 1]) <= 1e-4 {
+            run.pop();
+        }
+        if run.len() == 1 {
+            match style.cap {
+                LineCap::Round => add_polygon(&mut polygons, circle_polygon(run[0], half)),
+                LineCap::Square => {
+                    let (x, y) = run[0];
+                    add_polygon(&mut polygons, vec![(x - half, y - half), (x + half, y - half), (x + half, y + half), (x - half, y + half)]);
+                }
+                LineCap::Butt => {}
+            }
+            continue;
+        }
+        if run.is_empty() {
+            continue;
+        }
+
+        if !closed && style.cap == LineCap::Square {
+            let first = direction(run[1], run[0]);
+            run[0] = offset(run[0], first, half);
+            let last = run.len() - 1;
+            let end = direction(run[last - 1], run[last]);
+            run[last] = offset(run[last], end, half);
+        }
+
+        let segment_count = if closed { run.len() } else { run.len() - 1 };
+        for i in 0..segment_count {
+            let start = run[i];
+            let end = run[(i + 1) % run.len()];
+            let along = direction(start, end);
+            let normal = (-along.1, along.0);
+            add_polygon(&mut polygons, vec![
+                offset(start, normal, half),
+                offset(end, normal, half),
+                offset(end, normal, -half),
+                offset(start, normal, -half)
+            ]);
+        }
+
+        let join_vertices = if closed { 0..run.len() } else { 1..run.len() - 1 };
+        for i in join_vertices {
+            let previous = run[(i + run.len() - 1) % run.len()];
+            let next = run[(i + 1) % run.len()];
+            add_join(&mut polygons, run[i], direction(previous, run[i]), direction(run[i], next), half, style.join);
+        }
+
+        if !closed && style.cap == LineCap::Round {
+            add_polygon(&mut polygons, circle_polygon(run[0], half));
+            add_polygon(&mut polygons, circle_polygon(run[run.len() - 1], half));
+        }
+    }
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::RGB(0, 0, 0);
+
+    fn assert_close(actual: &[Vec<Point>], expected: &[Vec<Point>]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (run, expected_run) in actual.iter().zip(expected) {
+            assert_eq!(run.len(), expected_run.len(), "{:?}", actual);
+            for (a, b) in run.iter().zip(expected_run) {
+                assert!(distance(*a, *b) < 1e-4, "{:?} is not {:?}", actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn odd_dash_patterns_are_repeated() {
+        let pattern = DashPattern::new(&[3.0], 0.0);
+        assert_eq!(pattern.normalized_lengths(), vec![3.0, 3.0]);
+        assert_eq!(pattern.period(), 6.0);
+        assert_eq!(DashPattern::new(&[4.0, 2.0, 1.0], 0.0).normalized_lengths(), vec![4.0, 2.0, 1.0, 4.0, 2.0, 1.0]);
+        assert_eq!(DashPattern::new(&[4.0, 2.0], 0.0).normalized_lengths(), vec![4.0, 2.0]);
+
+        let runs = dash_polyline(&[(0.0, 0.0), (12.0, 0.0)], false, &pattern);
+        assert_close(&runs, &[vec![(0.0, 0.0), (3.0, 0.0)], vec![(6.0, 0.0), (9.0, 0.0)]]);
+    }
+
+    #[test]
+    fn dashes_carry_on_around_corners() {
+        let corner = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)];
+        let runs = dash_polyline(&corner, false, &DashPattern::new(&[6.0, 1.0], 0.0));
+        assert_close(&runs, &[vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0)], vec![(4.0, 3.0), (4.0, 4.0)]]);
+
+        // A gap over the corner picks up on the next segment where it left off
+        let runs = dash_polyline(&corner, false, &DashPattern::new(&[3.0, 2.0], 0.0));
+        assert_close(&runs, &[vec![(0.0, 0.0), (3.0, 0.0)], vec![(4.0, 1.0), (4.0, 4.0)]]);
+    }
+
+    #[test]
+    fn phase_shifts_the_pattern_along_the_line() {
+        let line = [(0.0, 0.0), (10.0, 0.0)];
+        let runs = dash_polyline(&line, false, &DashPattern::new(&[4.0, 2.0], 1.0));
+        assert_close(&runs, &[vec![(0.0, 0.0), (3.0, 0.0)], vec![(5.0, 0.0), (9.0, 0.0)]]);
+
+        // Starting inside a gap
+        let runs = dash_polyline(&line, false, &DashPattern::new(&[4.0, 2.0], 5.0));
+        assert_close(&runs, &[vec![(1.0, 0.0), (5.0, 0.0)], vec![(7.0, 0.0), (10.0, 0.0)]]);
+
+        // A whole period of phase changes nothing and negative phases wrap around
+        assert_eq!(
+            dash_polyline(&line, false, &DashPattern::new(&[4.0, 2.0], 7.0)),
+            dash_polyline(&line, false, &DashPattern::new(&[4.0, 2.0], 1.0))
+        );
+        let runs = dash_polyline(&line, false, &DashPattern::new(&[4.0, 2.0], -1.0));
+        assert_close(&runs, &[vec![(1.0, 0.0), (5.0, 0.0)], vec![(7.0, 0.0), (10.0, 0.0)]]);
+    }
+
+    #[test]
+    fn sharp_miters_fall_back_to_bevels() {
+        let style = StrokeStyle::new(BLACK, 2.0).with_join(LineJoin::Miter);
+        // Two segments and then the join
+        let right_angle = outline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false, &style);
+        assert_eq!(right_angle.len(), 3);
+        assert_eq!(right_angle[2].len(), 4);
+        let miter_tip = right_angle[2].iter().map(|&point| distance(point, (10.0, 0.0))).fold(0.0, f32::max);
+        assert!((miter_tip - 2f32.sqrt()).abs() < 1e-4);
+
+        let sharp = outline(&[(0.0, 0.0), (10.0, 0.0), (0.0, 1.0)], false, &style);
+        assert_eq!(sharp.len(), 3);
+        assert_eq!(sharp[2].len(), 3);
+    }
+
+    #[test]
+    fn caps_on_a_single_point() {
+        let square = outline(&[(5.0, 5.0)], false, &StrokeStyle::new(BLACK, 4.0).with_cap(LineCap::Square));
+        assert_eq!(square.len(), 1);
+        let mut corners = square[0].clone();
+        corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(corners, vec![(3.0, 3.0), (3.0, 7.0), (7.0, 3.0), (7.0, 7.0)]);
+
+        let round = outline(&[(5.0, 5.0)], false, &StrokeStyle::new(BLACK, 4.0).with_cap(LineCap::Round));
+        assert_eq!(round.len(), 1);
+        assert!(round[0].iter().all(|&point| (distance(point, (5.0, 5.0)) - 2.0).abs() < 1e-4));
+
+        assert!(outline(&[(5.0, 5.0)], false, &StrokeStyle::new(BLACK, 4.0)).is_empty());
+    }
+
+    #[test]
+    fn closed_polylines_do_not_repeat_the_closing_segment() {
+        let style = StrokeStyle::new(BLACK, 2.0);
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        // Four sides and four corners, whether or not the first point is repeated at the end
+        assert_eq!(outline(&square, true, &style).len(), 8);
+        let mut repeated = square.to_vec();
+        repeated.push(square[0]);
+        assert_eq!(outline(&repeated, true, &style).len(), 8);
+        // Left open it has three sides and the two inner corners
+        assert_eq!(outline(&square, false, &style).len(), 5);
+    }
+}
@@ -0,0 +1,277 @@
+use std::any::Any;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use crate::backend::{push_line, Backend};
+use crate::coordinate::PixelCoordinate2D;
+use crate::font::FontManager;
+use crate::path::{Path, PathSegment};
+use crate::stroke::{LineCap, LineJoin, StrokeStyle};
+use crate::error::GrapherError;
+
+const FONT_FAMILY: &str = "JetBrains Mono, DejaVu Sans Mono, monospace";
+
+/// Records everything drawn as SVG elements instead of pixels.
+/// Pixel coordinates are kept as is, strokes are offset by half a pixel so they land on the same pixels SDL would fill.
+pub struct SvgBackend {
+    width: u32,
+    height: u32,
+    elements: String,
+    /// The origin and clip rectangle of the group everything is currently drawn into, if there is one
+    view: Option<((i32, i32), Option<Rect>)>,
+    clip_count: usize
+}
+
+impl SvgBackend {
+    pub fn new(width: u32, height: u32) -> SvgBackend {
+        SvgBackend { width, height, elements: String::new(), view: None, clip_count: 0 }
+    }
+
+    /// The finished document.
+    pub fn to_svg(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}{}</svg>\n",
+            self.elements,
+            if self.view.is_some() { "</g>\n" } else { "" },
+            w = self.width,
+            h = self.height
+        )
+    }
+
+    fn open_view(&mut self) {
+        let Some((origin, clip)) = self.view else {
+            return;
+        };
+        let mut attributes = format!("transform=\"translate({} {})\"", origin.0, origin.1);
+        if let Some(clip) = clip {
+            // The clip path is in the coordinates of the group it is used on, after the translation
+            self.clip_count += 1;
+            push_line!(
+                self.elements,
+                "<clipPath id=\"clip{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+                self.clip_count, clip.x() - origin.0, clip.y() - origin.1, clip.width(), clip.height()
+            );
+            attributes.push_str(&format!(" clip-path=\"url(#clip{})\"", self.clip_count));
+        }
+        push_line!(self.elements, "<g {}>", attributes);
+    }
+}
+
+pub(crate) fn svg_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// The fill or stroke attribute for a color, with its opacity when it is not opaque.
+pub(crate) fn paint(attribute: &str, color: Color) -> String {
+    if color.a == 255 {
+        format!("{}=\"{}\"", attribute, svg_color(color))
+    } else {
+        format!("{}=\"{}\" {}-opacity=\"{}\"", attribute, svg_color(color), attribute, (color.a as f32 / 255.0 * 1000.0).round() / 1000.0)
+    }
+}
+
+pub(crate) fn stroke_attributes(style: &StrokeStyle) -> String {
+    let mut attributes = format!(
+        "fill=\"none\" {} stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"{}\"",
+        paint("stroke", style.color),
+        style.width,
+        match style.cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square"
+        },
+        match style.join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel"
+        }
+    );
+    if let Some(dash) = style.dash.as_ref().filter(|_| style.is_dashed()) {
+        let lengths: Vec<String> = dash.lengths.iter().map(|length| length.to_string()).collect();
+        attributes.push_str(&format!(" stroke-dasharray=\"{}\" stroke-dashoffset=\"{}\"", lengths.join(" "), dash.phase));
+    }
+    attributes
+}
+
+fn points_attribute(points: &[(f32, f32)]) -> String {
+    points.iter().map(|(x, y)| format!("{},{}", x + 0.5, y + 0.5)).collect::<Vec<_>>().join(" ")
+}
+
+fn path_data(path: &Path) -> String {
+    let point = |(x, y): (f32, f32)| format!("{},{}", x + 0.5, y + 0.5);
+    path.segments.iter().map(|segment| match *segment {
+        PathSegment::MoveTo(to) => format!("M{}", point(to)),
+        PathSegment::LineTo(to) => format!("L{}", point(to)),
+        PathSegment::QuadTo(control, to) => format!("Q{} {}", point(control), point(to)),
+        PathSegment::CubicTo(control1, control2, to) => format!("C{} {} {}", point(control1), point(control2), point(to)),
+        PathSegment::Close => "Z".to_string()
+    }).collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c)
+        }
+    }
+    escaped
+}
+
+impl Backend for SvgBackend {
+    fn size(&self) -> Result<(u32, u32), GrapherError> {
+        Ok((self.width, self.height))
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.elements.clear();
+        push_line!(self.elements, "<rect width=\"100%\" height=\"100%\" {}/>", paint("fill", color));
+        self.open_view();
+    }
+
+    fn present(&mut self) {}
+
+    fn draw_point(&mut self, position: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" {}/>",
+            position.x, position.y, paint("fill", color)
+        );
+        Ok(())
+    }
+
+    fn draw_line(&mut self, position1: PixelCoordinate2D, position2: PixelCoordinate2D, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-linecap=\"square\"/>",
+            position1.x as f32 + 0.5, position1.y as f32 + 0.5,
+            position2.x as f32 + 0.5, position2.y as f32 + 0.5,
+            paint("stroke", color)
+        );
+        Ok(())
+    }
+
+    fn draw_line_precise(&mut self, start: (f32, f32), end: (f32, f32), color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-linecap=\"square\"/>",
+            start.0 + 0.5, start.1 + 0.5, end.0 + 0.5, end.1 + 0.5, paint("stroke", color)
+        );
+        Ok(())
+    }
+
+    fn draw_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" {}/>",
+            position.x as f32 + 0.5, position.y as f32 + 0.5,
+            w.saturating_sub(1), h.saturating_sub(1),
+            paint("stroke", color)
+        );
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, position: PixelCoordinate2D, w: u32, h: u32, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            position.x, position.y, w, h, paint("fill", color)
+        );
+        Ok(())
+    }
+
+    fn draw_circle(&mut self, position: PixelCoordinate2D, radius: u32, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" {}/>",
+            position.x as f32 + 0.5, position.y as f32 + 0.5, radius, paint("stroke", color)
+        );
+        Ok(())
+    }
+
+    fn draw_text(&mut self, fonts: &mut FontManager, text: &str, position: PixelCoordinate2D, color: Color, size: u16) -> Result<(), GrapherError> {
+        // SVG places text by its baseline while SDL places it by the top of the line
+        push_line!(
+            self.elements,
+            "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" {} xml:space=\"preserve\">{}</text>",
+            position.x, position.y + fonts.text_ascent(text, size)?, FONT_FAMILY, size, paint("fill", color), escape(text)
+        );
+        Ok(())
+    }
+
+    fn draw_text_rotated(&mut self, fonts: &mut FontManager, text: &str, position: (f32, f32), color: Color, size: u16, rotation: f32) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<text x=\"{}\" y=\"{}\" transform=\"rotate({} {} {})\" font-family=\"{}\" font-size=\"{}\" {} xml:space=\"preserve\">{}</text>",
+            position.0, position.1 + fonts.text_ascent(text, size)? as f32,
+            -rotation.to_degrees(), position.0, position.1,
+            FONT_FAMILY, size, paint("fill", color), escape(text)
+        );
+        Ok(())
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], closed: bool, style: &StrokeStyle) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<{} points=\"{}\" {}/>",
+            if closed { "polygon" } else { "polyline" },
+            points_attribute(points),
+            stroke_attributes(style)
+        );
+        Ok(())
+    }
+
+    fn stroke_circle(&mut self, center: (f32, f32), radius: f32, style: &StrokeStyle) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            center.0 + 0.5, center.1 + 0.5, radius, stroke_attributes(style)
+        );
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<polygon points=\"{}\" {}/>",
+            points_attribute(points), paint("fill", color)
+        );
+        Ok(())
+    }
+
+    fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: Color) -> Result<(), GrapherError> {
+        push_line!(
+            self.elements,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            center.0 + 0.5, center.1 + 0.5, radius, paint("fill", color)
+        );
+        Ok(())
+    }
+
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle) -> Result<(), GrapherError> {
+        push_line!(self.elements, "<path d=\"{}\" {}/>", path_data(path), stroke_attributes(style));
+        Ok(())
+    }
+
+    fn fill_path(&mut self, path: &Path, color: Color) -> Result<(), GrapherError> {
+        push_line!(self.elements, "<path d=\"{}\" {}/>", path_data(path), paint("fill", color));
+        Ok(())
+    }
+
+    fn set_view(&mut self, origin: (i32, i32), clip: Option<Rect>) -> Result<(), GrapherError> {
+        if self.view.is_some() {
+            self.elements.push_str("</g>\n");
+        }
+        self.view = if origin == (0, 0) && clip.is_none() { None } else { Some((origin, clip)) };
+        self.open_view();
+        Ok(())
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
@@ -0,0 +1,162 @@
+use sdl2::pixels::Color;
+use crate::font::FontManager;
+use crate::math::{self, MathBox, MathRun};
+use crate::error::GrapherError;
+
+/// Which part of the text sits on the horizontal position it is drawn at.
+/// Every line of multi-line text is aligned on its own, like `text-align` in CSS.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HorizontalAnchor {
+    Left,
+    Center,
+    Right
+}
+
+/// Which part of the text sits on the vertical position it is drawn at.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VerticalAnchor {
+    Top,
+    /// Halfway down the whole block of lines
+    Middle,
+    /// The baseline of the first line
+    Baseline,
+    Bottom
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextStyle {
+    pub color: Color,
+    pub size: u16,
+    pub horizontal: HorizontalAnchor,
+    pub vertical: VerticalAnchor,
+    /// Radians counterclockwise around the anchor, a quarter turn reads bottom to top
+    pub rotation: f32,
+    /// Multiplies the font's own distance between lines
+    pub line_spacing: f32,
+    /// Reads every line as math markup, see the `math` module
+    pub math: bool
+}
+
+impl TextStyle {
+    pub fn new(color: Color, size: u16) -> TextStyle {
+        TextStyle {
+            color,
+            size,
+            horizontal: HorizontalAnchor::Left,
+            vertical: VerticalAnchor::Top,
+            rotation: 0.0,
+            line_spacing: 1.0,
+            math: false
+        }
+    }
+
+    pub fn with_anchor(mut self, horizontal: HorizontalAnchor, vertical: VerticalAnchor) -> TextStyle {
+        self.horizontal = horizontal;
+        self.vertical = vertical;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> TextStyle {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> TextStyle {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_math(mut self) -> TextStyle {
+        self.math = true;
+        self
+    }
+}
+
+/// A piece of text and where the top left of it goes, relative to the anchor and already rotated.
+pub struct TextRun {
+    pub text: String,
+    pub size: u16,
+    pub offset: (f32, f32)
+}
+
+/// A line drawn along with the text, like a fraction bar, relative to the anchor and already rotated.
+pub struct TextRule {
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    pub width: f32
+}
+
+pub struct TextLayout {
+    pub runs: Vec<TextRun>,
+    pub rules: Vec<TextRule>,
+    /// The size of the block of lines before rotation
+    pub width: f32,
+    pub height: f32
+}
+
+fn layout_line(fonts: &mut FontManager, line: &str, style: &TextStyle) -> Result<MathBox, GrapherError> {
+    if style.math {
+        return math::layout(fonts, line, style.size);
+    }
+    let width = if line.is_empty() { 0.0 } else { fonts.size_of(line, style.size)?.0 as f32 };
+    Ok(MathBox {
+        width,
+        ascent: fonts.ascent(style.size)? as f32,
+        descent: fonts.descent(style.size)? as f32,
+        runs: vec![MathRun { text: line.to_string(), size: style.size, position: (0.0, 0.0) }],
+        rules: Vec::new()
+    })
+}
+
+/// Splits the text into lines and places every piece of them around the anchor following the style.
+pub fn layout(fonts: &mut FontManager, text: &str, style: &TextStyle) -> Result<TextLayout, GrapherError> {
+    let line_height = fonts.line_height(style.size)? as f32 * style.line_spacing;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        lines.push(layout_line(fonts, line, style)?);
+    }
+    if lines.is_empty() {
+        lines.push(layout_line(fonts, "", style)?);
+    }
+
+    // Baselines going down from the top of the block, math lines with tall fractions push the next line down
+    let mut baselines = Vec::new();
+    let mut height = 0.0;
+    for line in &lines {
+        baselines.push(height + line.ascent);
+        height += line_height.max(line.ascent + line.descent);
+    }
+    let width = lines.iter().map(|line| line.width).fold(0.0, f32::max);
+    let top = match style.vertical {
+        VerticalAnchor::Top => 0.0,
+        VerticalAnchor::Middle => -height / 2.0,
+        VerticalAnchor::Baseline => -baselines[0],
+        VerticalAnchor::Bottom => -height
+    };
+
+    let (sin, cos) = style.rotation.sin_cos();
+    // Counterclockwise on screen, where y points down
+    let rotate = |(x, y): (f32, f32)| (x * cos + y * sin, y * cos - x * sin);
+    let mut output = TextLayout { runs: Vec::new(), rules: Vec::new(), width, height };
+    for (line, baseline) in lines.into_iter().zip(baselines) {
+        let x = match style.horizontal {
+            HorizontalAnchor::Left => 0.0,
+            HorizontalAnchor::Center => -line.width / 2.0,
+            HorizontalAnchor::Right => -line.width
+        };
+        let y = top + baseline;
+        for run in line.runs {
+            let ascent = fonts.text_ascent(&run.text, run.size)? as f32;
+            let offset = rotate((x + run.position.0, y + run.position.1 - ascent));
+            output.runs.push(TextRun { text: run.text, size: run.size, offset });
+        }
+        for rule in line.rules {
+            output.rules.push(TextRule {
+                start: rotate((x + rule.start.0, y + rule.start.1)),
+                end: rotate((x + rule.end.0, y + rule.end.1)),
+                width: rule.width
+            });
+        }
+    }
+    Ok(output)
+}
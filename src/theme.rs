@@ -0,0 +1,125 @@
+use sdl2::pixels::Color;
+use crate::stroke::{DashPattern, StrokeStyle};
+use crate::colors::{BLACK, CYAN, DARK_GRAY, GRAY, GREEN, LIGHT_GRAY, MAGENTA, ORANGE, PINK, RED, WHITE, YELLOW};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GridLineStyle {
+    Solid,
+    Dashed,
+    Dotted
+}
+
+/// How one kind of gridline is drawn.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridStyle {
+    pub color: Color,
+    pub line: GridLineStyle
+}
+
+impl GridStyle {
+    /// The same line as a one pixel stroke, for gridlines that are not straight like the rings of a polar axis.
+    pub fn stroke(&self) -> StrokeStyle {
+        let stroke = StrokeStyle::new(self.color, 1.0);
+        match self.line {
+            GridLineStyle::Solid => stroke,
+            GridLineStyle::Dashed => stroke.with_dash(DashPattern::new(&[6.0, 4.0], 0.0)),
+            GridLineStyle::Dotted => stroke.with_dash(DashPattern::new(&[1.0, 3.0], 0.0))
+        }
+    }
+}
+
+/// The colors everything built in draws with, so a whole plot can be switched between looks at once.
+/// Objects given a color of their own, like a function or a scatter plot, keep it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub background: Color,
+    /// Axis lines, vertices and sort bars
+    pub foreground: Color,
+    /// Guides drawn behind the data, like gridlines, crosshairs and construction lines
+    pub grid: Color,
+    pub text: Color,
+    /// Gridlines at the labelled ticks of an axis
+    pub major_grid: GridStyle,
+    /// Gridlines between the labelled ticks, fainter than the major ones
+    pub minor_grid: GridStyle,
+    /// Colors for data, used in order so neighbouring objects stay easy to tell apart
+    pub accents: [Color; 6],
+    /// What is being worked on right now, like the elements a sort is comparing
+    pub highlight: Color,
+    pub success: Color,
+    pub failure: Color
+}
+
+impl Theme {
+    /// Light lines on black, the original look and the default.
+    pub fn dark() -> Theme {
+        Theme {
+            background: BLACK,
+            foreground: WHITE,
+            grid: LIGHT_GRAY,
+            text: WHITE,
+            major_grid: GridStyle { color: DARK_GRAY, line: GridLineStyle::Solid },
+            minor_grid: GridStyle { color: Color::RGB(40, 40, 40), line: GridLineStyle::Dotted },
+            accents: [CYAN, YELLOW, GREEN, MAGENTA, ORANGE, PINK],
+            highlight: RED,
+            success: GREEN,
+            failure: RED
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            background: WHITE,
+            foreground: Color::RGB(32, 32, 32),
+            grid: GRAY,
+            text: Color::RGB(32, 32, 32),
+            major_grid: GridStyle { color: Color::RGB(200, 200, 200), line: GridLineStyle::Solid },
+            minor_grid: GridStyle { color: Color::RGB(232, 232, 232), line: GridLineStyle::Dotted },
+            accents: [
+                Color::RGB(31, 119, 180),
+                Color::RGB(255, 127, 14),
+                Color::RGB(44, 160, 44),
+                Color::RGB(214, 39, 40),
+                Color::RGB(148, 103, 189),
+                Color::RGB(140, 86, 75)
+            ],
+            highlight: Color::RGB(214, 39, 40),
+            success: Color::RGB(44, 160, 44),
+            failure: Color::RGB(214, 39, 40)
+        }
+    }
+
+    /// Black on white with accents that stay distinct for colour blind readers and on a grayscale printer.
+    pub fn print() -> Theme {
+        Theme {
+            background: WHITE,
+            foreground: BLACK,
+            grid: DARK_GRAY,
+            text: BLACK,
+            major_grid: GridStyle { color: GRAY, line: GridLineStyle::Dashed },
+            minor_grid: GridStyle { color: LIGHT_GRAY, line: GridLineStyle::Dotted },
+            accents: [
+                Color::RGB(0, 114, 178),
+                Color::RGB(213, 94, 0),
+                Color::RGB(0, 158, 115),
+                Color::RGB(204, 121, 167),
+                Color::RGB(230, 159, 0),
+                Color::RGB(86, 180, 233)
+            ],
+            highlight: Color::RGB(213, 94, 0),
+            success: Color::RGB(0, 158, 115),
+            failure: Color::RGB(213, 94, 0)
+        }
+    }
+
+    /// The accent for the `index`th object, starting over once every accent is used.
+    pub fn accent(&self, index: usize) -> Color {
+        self.accents[index % self.accents.len()]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
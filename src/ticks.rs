@@ -0,0 +1,336 @@
+use crate::scale::Scale;
+
+/// Tick positions along one axis, spaced by a "nice" step of 1, 2 or 5 times a power of ten,
+/// or at powers of ten for the log scales.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ticks {
+    pub values: Vec<f32>,
+    /// Zero for log scales, where the ticks are not evenly spaced
+    pub step: f32,
+    range: (f32, f32),
+    /// The step is `mantissa * 10^exponent`, kept apart so labels can be written without float noise
+    mantissa: i64,
+    exponent: i32,
+    spacing: Spacing
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Spacing {
+    Linear,
+    /// Every `stride`th power of ten
+    Log { stride: i32 },
+    /// Zero and every `stride`th power of ten from `10^first` outwards on both sides
+    SymLog { stride: i32, first: i32 }
+}
+
+/// Labels switch to scientific notation once the step is this many powers of ten away from 1.
+const SCIENTIFIC_EXPONENT: i32 = 5;
+
+impl Ticks {
+    /// Picks a step that gives about `target` ticks between `min` and `max`, both ends included when they land on a tick.
+    /// A range with `max` below `min` is empty and gets no ticks.
+    pub fn new(min: f32, max: f32, target: usize) -> Ticks {
+        let span = max - min;
+        if !span.is_finite() || span <= 0.0 || target == 0 {
+            return Ticks { values: Vec::new(), step: 0.0, range: (min, max), mantissa: 1, exponent: 0, spacing: Spacing::Linear };
+        }
+        let raw = span as f64 / target as f64;
+        let mut exponent = raw.log10().floor() as i32;
+        let fraction = raw / 10f64.powi(exponent);
+        let mantissa = if fraction <= 1.0 {
+            1
+        } else if fraction <= 2.0 {
+            2
+        } else if fraction <= 5.0 {
+            5
+        } else {
+            exponent += 1;
+            1
+        };
+        let step = mantissa as f64 * 10f64.powi(exponent);
+        // Counting whole steps from zero keeps every value an exact multiple instead of piling up rounding
+        let first = (min as f64 / step).ceil() as i64;
+        let last = (max as f64 / step + 1e-6).floor() as i64;
+        let values = (first..=last).map(|k| (k as f64 * step) as f32).collect();
+        Ticks { values, step: step as f32, range: (min, max), mantissa, exponent, spacing: Spacing::Linear }
+    }
+
+    /// Ticks suited to how the axis spreads its values. Log scales zoomed in to less than two powers of ten
+    /// fall back to the evenly spaced ticks, which read better there.
+    pub fn for_scale(scale: Scale, min: f32, max: f32, target: usize) -> Ticks {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        let ticks = match scale {
+            Scale::Linear => return Ticks::new(min, max, target),
+            Scale::Log10 | Scale::Ln => Ticks::log(min, max, target),
+            Scale::SymLog { threshold } => Ticks::symlog(min, max, threshold, target)
+        };
+        if ticks.values.len() < 2 {
+            Ticks::new(min, max, target)
+        } else {
+            ticks
+        }
+    }
+
+    fn with_spacing(values: Vec<f32>, range: (f32, f32), spacing: Spacing) -> Ticks {
+        Ticks { values, step: 0.0, range, mantissa: 1, exponent: 0, spacing }
+    }
+
+    /// The powers of ten from `min` to `max`, both positive.
+    fn decades(min: f32, max: f32) -> (i32, i32) {
+        // A little slack so a range ending right on a power of ten keeps it despite rounding
+        let first = ((min as f64).log10() - 1e-6).ceil() as i32;
+        let last = ((max as f64).log10() + 1e-6).floor() as i32;
+        (first, last)
+    }
+
+    fn log(min: f32, max: f32, target: usize) -> Ticks {
+        if max <= 0.0 || target == 0 {
+            return Ticks::with_spacing(Vec::new(), (min, max), Spacing::Log { stride: 1 });
+        }
+        let min = min.max(f32::MIN_POSITIVE);
+        let (first, last) = Ticks::decades(min, max);
+        let count = (last - first + 1).max(0);
+        let stride = (count as f32 / target as f32).ceil().max(1.0) as i32;
+        let values = (first..=last)
+            .filter(|k| k.rem_euclid(stride) == 0)
+            .map(|k| 10f64.powi(k) as f32)
+            .collect();
+        Ticks::with_spacing(values, (min, max), Spacing::Log { stride })
+    }
+
+    fn symlog(min: f32, max: f32, threshold: f32, target: usize) -> Ticks {
+        // Without a usable threshold there are no decades to count, the evenly spaced ticks are used instead
+        if target == 0 || !(threshold.is_finite() && threshold > 0.0) {
+            return Ticks::with_spacing(Vec::new(), (min, max), Spacing::SymLog { stride: 1, first: 0 });
+        }
+        // Inside the threshold the scale is close to linear, so decades start at the first one past it
+        let first = (threshold as f64).log10().ceil() as i32;
+        let largest = min.abs().max(max.abs());
+        let last = if largest > 0.0 { Ticks::decades(largest, largest).1 } else { first - 1 };
+        let count = 2 * (last - first + 1).max(0) + 1;
+        let stride = (count as f32 / target as f32).ceil().max(1.0) as i32;
+        let positive: Vec<f32> = (first..=last)
+            .filter(|k| (k - first) % stride == 0)
+            .map(|k| 10f64.powi(k) as f32)
+            .collect();
+        let values = positive.iter().rev().map(|value| -value)
+            .chain(std::iter::once(0.0))
+            .chain(positive.iter().copied())
+            .filter(|value| *value >= min && *value <= max)
+            .collect();
+        Ticks::with_spacing(values, (min, max), Spacing::SymLog { stride, first })
+    }
+
+    /// Positions between the ticks, splitting a step of 1 or 5 into fifths and a step of 2 into quarters
+    /// so minor ticks also land on round numbers.
+    /// For the log scales these are 2 to 9 times each power of ten, or the skipped powers when there are too many to label.
+    pub fn minor(&self) -> Vec<f32> {
+        match self.spacing {
+            Spacing::Linear => {},
+            Spacing::Log { stride } => {
+                let (first, last) = Ticks::decades(self.range.0, self.range.1);
+                return self.minor_decades(first - 1, last, stride, 0, 1.0);
+            },
+            Spacing::SymLog { stride, first } => {
+                let largest = self.range.0.abs().max(self.range.1.abs());
+                if largest <= 0.0 {
+                    return Vec::new();
+                }
+                let last = Ticks::decades(largest, largest).1;
+                let mut values = self.minor_decades(first, last, stride, first, -1.0);
+                values.reverse();
+                values.extend(self.minor_decades(first, last, stride, first, 1.0));
+                return values;
+            }
+        }
+        if self.step == 0.0 {
+            return Vec::new();
+        }
+        let divisions = if self.mantissa == 2 { 4 } else { 5 };
+        let step = self.mantissa as f64 * 10f64.powi(self.exponent) / divisions as f64;
+        let first = (self.range.0 as f64 / step).ceil() as i64;
+        let last = (self.range.1 as f64 / step + 1e-6).floor() as i64;
+        (first..=last)
+            .filter(|k| k % divisions != 0)
+            .map(|k| (k as f64 * step) as f32)
+            .collect()
+    }
+
+    /// Minor ticks from `10^first` up to the decade after `last`, on the side of zero given by `sign`.
+    fn minor_decades(&self, first: i32, last: i32, stride: i32, offset: i32, sign: f64) -> Vec<f32> {
+        let in_range = |value: f64| value >= self.range.0 as f64 && value <= self.range.1 as f64;
+        let mut values = Vec::new();
+        for k in first..=last {
+            if stride > 1 {
+                if (k - offset).rem_euclid(stride) != 0 && in_range(sign * 10f64.powi(k)) {
+                    values.push((sign * 10f64.powi(k)) as f32);
+                }
+                continue;
+            }
+            for m in 2..10 {
+                let value = sign * m as f64 * 10f64.powi(k);
+                if in_range(value) {
+                    values.push(value as f32);
+                }
+            }
+        }
+        values
+    }
+
+    /// Writes a tick value with just enough decimals for the step, or in scientific notation for very large or small steps.
+    pub fn label(&self, value: f32) -> String {
+        if self.spacing != Spacing::Linear {
+            return decade_label(value);
+        }
+        // The value as a whole number of mantissas times 10^exponent
+        let steps = (value as f64 / self.step as f64).round() as i64;
+        let mut digits = steps * self.mantissa;
+        if digits == 0 {
+            return "0".to_string();
+        }
+        let mut exponent = self.exponent;
+        if exponent.abs() >= SCIENTIFIC_EXPONENT {
+            while digits % 10 == 0 {
+                digits /= 10;
+                exponent += 1;
+            }
+            let sign = if digits < 0 { "-" } else { "" };
+            let digits = digits.unsigned_abs().to_string();
+            let exponent = exponent + digits.len() as i32 - 1;
+            return if digits.len() == 1 {
+                format!("{}{}e{}", sign, digits, exponent)
+            } else {
+                format!("{}{}.{}e{}", sign, &digits[..1], &digits[1..], exponent)
+            };
+        }
+        if exponent >= 0 {
+            (digits * 10i64.pow(exponent as u32)).to_string()
+        } else {
+            format!("{:.*}", (-exponent) as usize, digits as f64 * 10f64.powi(exponent))
+        }
+    }
+}
+
+/// Labels a power of ten, or its negative, the same way evenly spaced ticks would.
+fn decade_label(value: f32) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    let exponent = (value.abs() as f64).log10().round() as i32;
+    if exponent.abs() >= SCIENTIFIC_EXPONENT {
+        format!("{}1e{}", sign, exponent)
+    } else if exponent >= 0 {
+        format!("{}{}", sign, 10i64.pow(exponent as u32))
+    } else {
+        format!("{}{:.*}", sign, (-exponent) as usize, 10f64.powi(exponent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(ticks: &Ticks) -> Vec<String> {
+        ticks.values.iter().map(|&value| ticks.label(value)).collect()
+    }
+
+    #[test]
+    fn steps_are_one_two_or_five() {
+        let ticks = Ticks::new(0.0, 10.0, 5);
+        assert_eq!(ticks.step, 2.0);
+        assert_eq!(ticks.values, vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(Ticks::new(0.0, 1.0, 10).step, 0.1);
+        assert_eq!(Ticks::new(0.0, 100.0, 4).step, 50.0);
+        assert_eq!(Ticks::new(0.0, 7.0, 1).step, 10.0);
+    }
+
+    #[test]
+    fn reversed_and_empty_ranges_have_no_ticks() {
+        assert!(Ticks::new(10.0, 0.0, 5).values.is_empty());
+        assert!(Ticks::new(3.0, 3.0, 5).values.is_empty());
+        assert!(Ticks::new(0.0, 10.0, 0).values.is_empty());
+        assert!(Ticks::new(0.0, f32::INFINITY, 5).values.is_empty());
+        assert!(Ticks::new(3.0, 3.0, 5).minor().is_empty());
+    }
+
+    #[test]
+    fn labels_have_no_float_noise() {
+        let ticks = Ticks::new(0.0, 1.0, 10);
+        assert_eq!(labels(&ticks), ["0", "0.1", "0.2", "0.3", "0.4", "0.5", "0.6", "0.7", "0.8", "0.9", "1.0"]);
+        let ticks = Ticks::new(0.0, 0.06, 3);
+        assert_eq!(labels(&ticks), ["0", "0.02", "0.04", "0.06"]);
+    }
+
+    #[test]
+    fn labels_switch_to_scientific_at_1e5() {
+        let ticks = Ticks::new(0.0, 4e4, 4);
+        assert_eq!(ticks.step, 1e4);
+        assert_eq!(labels(&ticks), ["0", "10000", "20000", "30000", "40000"]);
+        let ticks = Ticks::new(0.0, 4e5, 4);
+        assert_eq!(labels(&ticks), ["0", "1e5", "2e5", "3e5", "4e5"]);
+        let ticks = Ticks::new(0.0, 2.5e-5, 5);
+        assert_eq!(ticks.label(ticks.values[1]), "5e-6");
+        let ticks = Ticks::new(0.0, 3e6, 6);
+        assert_eq!(ticks.label(1.5e6), "1.5e6");
+    }
+
+    #[test]
+    fn negative_values() {
+        let ticks = Ticks::new(-1.0, 0.5, 3);
+        assert_eq!(ticks.step, 0.5);
+        assert_eq!(labels(&ticks), ["-1.0", "-0.5", "0", "0.5"]);
+        let ticks = Ticks::new(-250.0, -50.0, 4);
+        assert_eq!(labels(&ticks), ["-250", "-200", "-150", "-100", "-50"]);
+    }
+
+    #[test]
+    fn minor_ticks_split_the_step_and_skip_majors() {
+        let ticks = Ticks::new(0.0, 4.0, 2);
+        assert_eq!(ticks.step, 2.0);
+        assert_eq!(ticks.minor(), vec![0.5, 1.0, 1.5, 2.5, 3.0, 3.5]);
+        let ticks = Ticks::new(0.0, 1.0, 1);
+        assert_eq!(ticks.minor(), vec![0.2, 0.4, 0.6, 0.8]);
+    }
+
+    #[test]
+    fn log_ticks_land_on_decades() {
+        let ticks = Ticks::for_scale(Scale::Log10, 1.0, 1e6, 8);
+        assert_eq!(ticks.values, vec![1.0, 10.0, 100.0, 1e3, 1e4, 1e5, 1e6]);
+        assert_eq!(labels(&ticks), ["1", "10", "100", "1000", "10000", "1e5", "1e6"]);
+        assert_eq!(&ticks.minor()[..3], &[2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn log_ticks_below_one() {
+        let ticks = Ticks::for_scale(Scale::Ln, 1e-3, 10.0, 8);
+        assert_eq!(labels(&ticks), ["0.001", "0.01", "0.1", "1", "10"]);
+    }
+
+    #[test]
+    fn log_ticks_skip_decades_when_crowded() {
+        let ticks = Ticks::for_scale(Scale::Log10, 1.0, 1e12, 8);
+        assert_eq!(labels(&ticks), ["1", "100", "10000", "1e6", "1e8", "1e10", "1e12"]);
+        assert!(ticks.minor().contains(&10.0));
+    }
+
+    #[test]
+    fn log_ticks_within_a_decade_fall_back_to_even_steps() {
+        let ticks = Ticks::for_scale(Scale::Log10, 2.0, 8.0, 6);
+        assert_eq!(labels(&ticks), ["2", "3", "4", "5", "6", "7", "8"]);
+    }
+
+    #[test]
+    fn symlog_ticks_mirror_around_zero() {
+        let ticks = Ticks::for_scale(Scale::SymLog { threshold: 1.0 }, -100.0, 100.0, 8);
+        assert_eq!(labels(&ticks), ["-100", "-10", "-1", "0", "1", "10", "100"]);
+    }
+
+    #[test]
+    fn symlog_without_a_usable_threshold_falls_back_to_even_steps() {
+        for threshold in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let ticks = Ticks::for_scale(Scale::SymLog { threshold }, -10.0, 10.0, 5);
+            assert_eq!(ticks, Ticks::new(-10.0, 10.0, 5));
+        }
+    }
+}